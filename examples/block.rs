@@ -0,0 +1,14 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+fn main() {
+    println!("Waiting for Ctrl-C...");
+    ctrlc::block_until_ctrl_c().expect("Error setting Ctrl-C handler");
+    println!("done");
+}
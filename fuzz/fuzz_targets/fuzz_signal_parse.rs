@@ -0,0 +1,28 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#![no_main]
+
+use ctrlc::SignalType;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(name) = std::str::from_utf8(data) {
+        if let Ok(signal) = name.parse::<SignalType>() {
+            assert_eq!(signal.to_string().parse(), Ok(signal));
+        }
+    }
+
+    if let Ok(bytes) = data.try_into() {
+        let num = i32::from_ne_bytes(bytes);
+        if let Some(signal) = SignalType::from_raw(num) {
+            assert_eq!(signal.into_raw(), num);
+        }
+    }
+});
@@ -0,0 +1,172 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::error::Error;
+use crate::{ConflictPolicy, HandlerGuard};
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub(crate) type PanicCallback = Arc<dyn Fn(Box<dyn Any + Send>) + Send + Sync>;
+
+/// Configuration of the signal handling thread, in the style of `std::thread::Builder`.
+///
+/// The free functions such as [`set_handler()`](fn.set_handler.html) use the defaults: a thread
+/// named `ctrl-c` with the stack size set by
+/// [`set_handler_stack_size()`](fn.set_handler_stack_size.html), if any.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::HandlerBuilder::new()
+///     .name("shutdown".into())
+///     .stack_size(64 * 1024)
+///     .set_handler(|| println!("Hello world!"))
+///     .expect("Error setting Ctrl-C handler");
+/// ```
+#[derive(Default, Clone)]
+pub struct HandlerBuilder {
+    pub(crate) name: Option<String>,
+    pub(crate) stack_size: Option<usize>,
+    pub(crate) on_panic: Option<PanicCallback>,
+    pub(crate) coalesce: Option<Duration>,
+    #[cfg(unix)]
+    pub(crate) sigaltstack: Option<usize>,
+}
+
+impl fmt::Debug for HandlerBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut f = f.debug_struct("HandlerBuilder");
+        f.field("name", &self.name)
+            .field("stack_size", &self.stack_size)
+            .field("on_panic", &self.on_panic.is_some())
+            .field("coalesce", &self.coalesce);
+        #[cfg(unix)]
+        f.field("sigaltstack", &self.sigaltstack);
+        f.finish()
+    }
+}
+
+impl HandlerBuilder {
+    /// Create a builder with the default configuration.
+    #[must_use = "the builder does nothing until a handler is set with it"]
+    pub fn new() -> HandlerBuilder {
+        HandlerBuilder::default()
+    }
+
+    /// Name the signal handling thread.
+    #[must_use = "builder methods return a new builder instead of modifying it"]
+    pub fn name(mut self, name: String) -> HandlerBuilder {
+        self.name = Some(name);
+        self
+    }
+
+    /// Set the stack size of the signal handling thread, in bytes. Must be at least `SIGSTKSZ`
+    /// on Unix.
+    #[must_use = "builder methods return a new builder instead of modifying it"]
+    pub fn stack_size(mut self, size: usize) -> HandlerBuilder {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// Install an alternate signal stack of `size` bytes for the signal handling thread, with
+    /// [`unix::configure_sigaltstack()`](unix/fn.configure_sigaltstack.html).
+    ///
+    /// The OS handler of ctrlc then runs on that stack when a signal is delivered to the signal
+    /// handling thread. Setting the handler fails if the stack can not be installed, e.g. if
+    /// `size` is less than `MINSIGSTKSZ`.
+    #[cfg(unix)]
+    #[must_use = "builder methods return a new builder instead of modifying it"]
+    pub fn sigaltstack(mut self, size: usize) -> HandlerBuilder {
+        self.sigaltstack = Some(size);
+        self
+    }
+
+    /// Execute the handler at most once per `min_interval`.
+    ///
+    /// Signals received within `min_interval` of the last execution of the handler are dropped,
+    /// so that a burst of signals, e.g. repeated `SIGHUP`s to reload a configuration, results in
+    /// a single execution.
+    #[must_use = "builder methods return a new builder instead of modifying it"]
+    pub fn coalesce(mut self, min_interval: Duration) -> HandlerBuilder {
+        self.coalesce = Some(min_interval);
+        self
+    }
+
+    /// Catch panics in the handler and pass their payload to `callback`.
+    ///
+    /// The callback runs on the signal handling thread, which then stops like it would for an
+    /// uncaught panic. The handler stays registered but is no longer executed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// ctrlc::HandlerBuilder::new()
+    ///     .on_panic(|_| eprintln!("Ctrl-C handler panicked"))
+    ///     .set_handler(|| panic!("Hello world!"))
+    ///     .expect("Error setting Ctrl-C handler");
+    /// ```
+    #[must_use = "builder methods return a new builder instead of modifying it"]
+    pub fn on_panic<F>(mut self, callback: F) -> HandlerBuilder
+    where
+        F: Fn(Box<dyn Any + Send>) + 'static + Send + Sync,
+    {
+        self.on_panic = Some(Arc::new(callback));
+        self
+    }
+
+    /// The same as ctrlc::set_handler but with this configuration.
+    ///
+    /// # Errors
+    /// Will return an error if the stack size is invalid or if a system error occurred while
+    /// setting the handler.
+    pub fn set_handler<F>(&self, user_handler: F) -> Result<(), Error>
+    where
+        F: FnMut() + 'static + Send,
+    {
+        crate::init_and_set_handler_with(self, user_handler, ConflictPolicy::Overwrite)
+            .map(HandlerGuard::detach)
+    }
+
+    /// The same as ctrlc::try_set_handler but with this configuration.
+    ///
+    /// # Errors
+    /// Will return an error if another handler exists, if the stack size is invalid or if a
+    /// system error occurred while setting the handler.
+    pub fn try_set_handler<F>(&self, user_handler: F) -> Result<(), Error>
+    where
+        F: FnMut() + 'static + Send,
+    {
+        crate::init_and_set_handler_with(self, user_handler, ConflictPolicy::ErrorOnCustomOnly)
+            .map(HandlerGuard::detach)
+    }
+
+    /// The same as ctrlc::set_scoped_handler but with this configuration.
+    ///
+    /// # Errors
+    /// Will return an error if the stack size is invalid or if a system error occurred while
+    /// setting the handler.
+    pub fn set_scoped_handler<F>(&self, user_handler: F) -> Result<HandlerGuard, Error>
+    where
+        F: FnMut() + 'static + Send,
+    {
+        crate::init_and_set_handler_with(self, user_handler, ConflictPolicy::Overwrite)
+    }
+
+    /// The same as ctrlc::try_set_scoped_handler but with this configuration.
+    ///
+    /// # Errors
+    /// Will return an error if another handler exists, if the stack size is invalid or if a
+    /// system error occurred while setting the handler.
+    pub fn try_set_scoped_handler<F>(&self, user_handler: F) -> Result<HandlerGuard, Error>
+    where
+        F: FnMut() + 'static + Send,
+    {
+        crate::init_and_set_handler_with(self, user_handler, ConflictPolicy::ErrorOnCustomOnly)
+    }
+}
@@ -6,8 +6,10 @@ use std::fmt;
 pub enum Error {
     /// Signal could not be found from the system.
     NoSuchSignal(crate::SignalType),
-    /// Ctrl-C signal handler already registered.
-    MultipleHandlers,
+    /// A handler is already registered for the contained signal, either by ctrlc or, with
+    /// [`try_set_handler()`](fn.try_set_handler.html) and
+    /// [`ConflictPolicy`](enum.ConflictPolicy.html), by other code.
+    MultipleHandlers(crate::Signal),
     /// Unexpected system error.
     System(std::io::Error),
 }
@@ -16,27 +18,56 @@ impl Error {
     fn describe(&self) -> &str {
         match *self {
             Error::NoSuchSignal(_) => "Signal could not be found from the system",
-            Error::MultipleHandlers => "Ctrl-C signal handler already registered",
+            Error::MultipleHandlers(_) => "Ctrl-C signal handler already registered",
             Error::System(_) => "Unexpected system error",
         }
     }
 }
 
-impl From<platform::Error> for Error {
-    fn from(e: platform::Error) -> Error {
-        #[cfg(not(windows))]
-        if e == platform::Error::EEXIST {
-            return Error::MultipleHandlers;
+impl Error {
+    /// Whether this is an error reported by the OS, i.e. an [`Error::System`] carrying an OS
+    /// error code.
+    #[must_use = "checking the error kind has no side effects"]
+    pub fn is_os_error(&self) -> bool {
+        match *self {
+            Error::System(ref e) => e.raw_os_error().is_some(),
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        match (self, other) {
+            (Error::NoSuchSignal(a), Error::NoSuchSignal(b)) => a == b,
+            (Error::MultipleHandlers(a), Error::MultipleHandlers(b)) => a == b,
+            // io::Error is not comparable, compare what identifies the error instead.
+            (Error::System(a), Error::System(b)) => {
+                a.kind() == b.kind() && a.raw_os_error() == b.raw_os_error()
+            }
+            _ => false,
         }
+    }
+}
 
-        let system_error = std::io::Error::new(std::io::ErrorKind::Other, e);
-        Error::System(system_error)
+impl From<platform::Error> for Error {
+    fn from(e: platform::Error) -> Error {
+        // Keeps the OS error code available through raw_os_error(). The platform error already
+        // is an io::Error on Windows.
+        #[allow(clippy::useless_conversion)]
+        Error::System(e.into())
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Ctrl-C error: {}", self.describe())
+        match *self {
+            Error::MultipleHandlers(signal) => match platform::signal_name(signal) {
+                Some(name) => write!(f, "Ctrl-C error: Signal {} already has a handler", name),
+                None => write!(f, "Ctrl-C error: Signal {} already has a handler", signal),
+            },
+            _ => write!(f, "Ctrl-C error: {}", self.describe()),
+        }
     }
 }
 
@@ -45,7 +76,7 @@ impl std::error::Error for Error {
         self.describe()
     }
 
-    fn cause(&self) -> Option<&dyn std::error::Error> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
             Error::System(ref e) => Some(e),
             _ => None,
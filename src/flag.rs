@@ -0,0 +1,79 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Flag based signal handling, in the style of `signal_hook::flag`.
+//!
+//! Instead of running a closure, the handler sets an atomic flag that the application polls.
+//!
+//! # Example
+//! ```no_run
+//! use std::sync::atomic::{AtomicBool, Ordering};
+//! use std::sync::Arc;
+//!
+//! let term = Arc::new(AtomicBool::new(false));
+//! ctrlc::flag::register(ctrlc::SignalType::Ctrlc, Arc::clone(&term))
+//!     .expect("Error setting Ctrl-C handler");
+//!
+//! while !term.load(Ordering::SeqCst) {
+//!     // Do work
+//! }
+//! ```
+
+use crate::error::Error;
+use crate::signal::SignalType;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Register a handler that sets `flag` to `true` when `signal` is received.
+///
+/// Like [`set_handler()`](../fn.set_handler.html), this starts the signal handling thread and can
+/// only be done once. The other signals handled by ctrlc still reach the handler thread, but
+/// leave `flag` untouched.
+///
+/// # Errors
+/// Will return [`Error::NoSuchSignal`] if `signal` is not handled by ctrlc (see the
+/// `termination` feature on Unix), or an error if a system error occurred while setting the
+/// handler.
+pub fn register(signal: SignalType, flag: Arc<AtomicBool>) -> Result<(), Error> {
+    if !signal.is_handled() {
+        return Err(Error::NoSuchSignal(signal));
+    }
+
+    crate::set_handler_with_info(move |info| {
+        if info.signal == signal {
+            flag.store(true, Ordering::SeqCst);
+        }
+    })
+}
+
+/// Register a handler that stores `value` to `flag` when `signal` is received.
+///
+/// This allows telling apart which of several events happened, when combined with other code
+/// storing different values to the same flag. Other signals handled by ctrlc leave `flag`
+/// untouched.
+///
+/// # Errors
+/// Will return [`Error::NoSuchSignal`] if `signal` is not handled by ctrlc (see the
+/// `termination` feature on Unix), or an error if a system error occurred while setting the
+/// handler.
+pub fn register_usize(
+    signal: SignalType,
+    flag: Arc<AtomicUsize>,
+    value: usize,
+) -> Result<(), Error> {
+    if !signal.is_handled() {
+        return Err(Error::NoSuchSignal(signal));
+    }
+
+    crate::set_handler_with_info(move |info| {
+        if info.signal == signal {
+            flag.store(value, Ordering::SeqCst);
+        }
+    })
+}
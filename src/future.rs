@@ -0,0 +1,112 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::error::Error;
+use crate::HandlerGuard;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Default)]
+struct State {
+    fired: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+/// Future returned by [`ctrl_c()`](fn.ctrl_c.html).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct CtrlC {
+    state: Option<Arc<State>>,
+    // Removes the handler when the future is dropped. Not dropped in poll(), as that joins the
+    // handler thread and poll() must not block.
+    _guard: Option<HandlerGuard>,
+    error: Option<Error>,
+}
+
+/// Wait for the first Ctrl-C asynchronously.
+///
+/// Registers the Ctrl-C handler and returns a future that resolves once the first Ctrl-C is
+/// received. The future works with any executor, the signal handling thread wakes the task
+/// waiting for it.
+///
+/// The handler is removed when the future is dropped, which `.await` does right after it
+/// resolves. Ctrl-C then terminates the process again and `ctrl_c()` can be called anew. Only one
+/// handler can be registered at a time though, so the future resolves to an error if another one
+/// exists.
+///
+/// # Example
+/// ```no_run
+/// async fn run() {
+///     println!("Waiting for Ctrl-C...");
+///     ctrlc::ctrl_c().await.expect("Error waiting for Ctrl-C");
+///     println!("Got it! Exiting...");
+/// }
+/// ```
+///
+/// # Errors
+/// The future resolves to [`Error::MultipleHandlers`] if a handler is already registered, or to
+/// another error if a system error occurred while setting the handler.
+///
+/// # Panics
+/// Like most futures, the future panics if it is polled again after it has resolved.
+pub fn ctrl_c() -> CtrlC {
+    let state = Arc::new(State::default());
+    let handler_state = Arc::clone(&state);
+
+    let handler = move || {
+        if !handler_state.fired.swap(true, Ordering::SeqCst) {
+            if let Some(waker) = handler_state.waker.lock().unwrap().take() {
+                waker.wake();
+            }
+        }
+    };
+
+    match crate::init_and_set_handler(handler, crate::ConflictPolicy::Overwrite) {
+        Ok(guard) => CtrlC {
+            state: Some(state),
+            _guard: Some(guard),
+            error: None,
+        },
+        Err(e) => CtrlC {
+            state: None,
+            _guard: None,
+            error: Some(e),
+        },
+    }
+}
+
+impl Future for CtrlC {
+    type Output = Result<(), Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Some(e) = this.error.take() {
+            return Poll::Ready(Err(e));
+        }
+
+        let state = this
+            .state
+            .as_ref()
+            .expect("CtrlC future polled after completion");
+
+        // Store the waker before checking the flag so a signal arriving in between is not
+        // missed.
+        *state.waker.lock().unwrap() = Some(cx.waker().clone());
+
+        if state.fired.load(Ordering::SeqCst) {
+            this.state = None;
+            return Poll::Ready(Ok(()));
+        }
+
+        Poll::Pending
+    }
+}
@@ -8,6 +8,11 @@
 // according to those terms.
 
 #![warn(missing_docs)]
+#![warn(
+    clippy::must_use_candidate,
+    clippy::double_must_use,
+    clippy::return_self_not_must_use
+)]
 
 //! Cross platform handling of Ctrl-C signals.
 //!
@@ -21,6 +26,11 @@
 //! execute the handler each time we receive a `Ctrl+C` signal. There can only be
 //! one handler, you would typically set one at the start of your program.
 //!
+//! [set_scoped_handler()](fn.set_scoped_handler.html) sets a handler that is removed again when
+//! the returned [HandlerGuard](struct.HandlerGuard.html) is dropped, and
+//! [remove_all_handlers()](fn.remove_all_handlers.html) removes any handler. This is mostly useful
+//! in tests and in libraries that only need to handle Ctrl-C temporarily.
+//!
 //! # Example
 //! ```no_run
 //! # #[allow(clippy::needless_doctest_main)]
@@ -41,26 +51,85 @@
 //! }
 //! ```
 //!
-//! # Handling SIGTERM and SIGHUP
-//! Handling of `SIGTERM and SIGHUP` can be enabled with `termination` feature. If this is enabled,
-//! the handler specified by `set_handler()` will be executed for `SIGINT`, `SIGTERM` and `SIGHUP`.
+//! # Handling SIGTERM, SIGHUP and SIGQUIT
+//! Handling of `SIGTERM`, `SIGHUP` and `SIGQUIT` can be enabled with `termination` feature. If this
+//! is enabled, the handler specified by `set_handler()` will be executed for `SIGINT`, `SIGTERM`,
+//! `SIGHUP` and `SIGQUIT`.
+//! On Windows, every console control event runs the handler regardless of features. Besides
+//! `CTRL_C_EVENT` and `CTRL_BREAK_EVENT` this includes `CTRL_CLOSE_EVENT`, `CTRL_LOGOFF_EVENT`
+//! and `CTRL_SHUTDOWN_EVENT`.
+//! `SignalType::WindowChange` (`SIGWINCH`), `SignalType::UserDefined1` (`SIGUSR1`) and
+//! `SignalType::UserDefined2` (`SIGUSR2`) are available on Unix regardless of features, e.g. for
+//! [unix::SignalFd](unix/struct.SignalFd.html), but are not handled by `set_handler()`.
+//!
+//! # signalfd
+//! On Linux and Android, the `signalfd` feature receives signals through a `signalfd(2)` instead
+//! of a signal handler. The file descriptor is available from
+//! [unix::signal_fd()](unix/fn.signal_fd.html) for use with `epoll` or an async reactor. The
+//! handled signals are blocked by `set_handler()` in the calling thread, so it should be called
+//! before any other threads are spawned. Removing the handler unblocks them again only if done
+//! from that same thread, e.g. by dropping the [HandlerGuard](struct.HandlerGuard.html) there.
+//! Otherwise they stay blocked and Ctrl-C is ignored by the threads that block it.
+//!
+//! # Tracing
+//! With the `tracing` feature, [set_handler_with_span()](fn.set_handler_with_span.html) runs the
+//! handler inside a `tracing` span.
+//!
+//! # Windows services
+//! Windows services receive stop requests from the service control manager instead of console
+//! control events. With the `windows-service` feature, `set_handler()` called from the service
+//! main function also receives them. The status handle needed to report the service status is
+//! available from [windows::service_status_handle()](windows/fn.service_status_handle.html).
+//!
+//! # Testing
+//! The `test-utils` feature enables the [testing](testing/index.html) module, which allows
+//! delivering signals to the handler without raising them through the OS.
 //!
 
-#[macro_use]
-
+mod builder;
+pub use builder::HandlerBuilder;
 mod error;
+pub mod flag;
+mod future;
+pub use future::{ctrl_c, CtrlC};
 mod platform;
 pub use platform::Signal;
+pub mod prelude;
 mod signal;
 pub use signal::*;
+pub mod sys;
+#[cfg(feature = "test-utils")]
+pub mod testing;
+#[cfg(unix)]
+pub mod unix;
+pub mod util;
+#[cfg(windows)]
+pub mod windows;
 
 pub use error::Error;
+use std::io;
+use std::mem;
+use std::panic;
+use std::process;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
 use std::thread;
+use std::time::{Duration, Instant};
 
 static INIT: AtomicBool = AtomicBool::new(false);
 static INIT_LOCK: Mutex<()> = Mutex::new(());
+static HANDLER_THREAD: Mutex<Option<HandlerThread>> = Mutex::new(None);
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static HANDLER_STACK_SIZE: AtomicUsize = AtomicUsize::new(0);
+// Custom names of the signal handling thread, leaked once per distinct name so that
+// handler_thread_name() can return them without allocating.
+static THREAD_NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+// Number of signals received by the handler thread, notified on SIGNAL_RECEIVED whenever it
+// changes and when the handler is removed.
+static SIGNALS_RECEIVED: Mutex<u64> = Mutex::new(0);
+static SIGNAL_RECEIVED: Condvar = Condvar::new();
 
 /// Register signal handler for Ctrl-C.
 ///
@@ -73,8 +142,8 @@ static INIT_LOCK: Mutex<()> = Mutex::new(());
 /// ```
 ///
 /// # Warning
-/// On Unix, the handler registration for `SIGINT`, (`SIGTERM` and `SIGHUP` if termination feature
-/// is enabled) or `SA_SIGINFO` posix signal handlers will be overwritten. On Windows, multiple
+/// On Unix, the handler registration for `SIGINT`, (`SIGTERM`, `SIGHUP` and `SIGQUIT` if
+/// termination feature is enabled) or `SA_SIGINFO` posix signal handlers will be overwritten. On Windows, multiple
 /// handler routines are allowed, but they are called on a last-registered, first-called basis
 /// until the signal is handled.
 ///
@@ -90,15 +159,20 @@ static INIT_LOCK: Mutex<()> = Mutex::new(());
 ///
 /// # Panics
 /// Any panic in the handler will not be caught and will cause the signal handler thread to stop.
+/// Use [`set_handler_with_panic_propagation()`](fn.set_handler_with_panic_propagation.html) or
+/// [`HandlerBuilder::on_panic()`](struct.HandlerBuilder.html#method.on_panic) to be notified.
 pub fn set_handler<F>(user_handler: F) -> Result<(), Error>
 where
     F: FnMut() + 'static + Send,
 {
-    init_and_set_handler(user_handler, true)
+    init_and_set_handler(user_handler, ConflictPolicy::Overwrite).map(HandlerGuard::detach)
 }
 
 /// The same as ctrlc::set_handler but errors if a handler already exists for the signal(s).
 ///
+/// On Unix, signals that are ignored (`SIG_IGN`) are not considered to have a handler, see
+/// [`ConflictPolicy::ErrorOnCustomOnly`](enum.ConflictPolicy.html#variant.ErrorOnCustomOnly).
+///
 /// # Errors
 /// Will return an error if another handler exists or if a system error occurred while setting the
 /// handler.
@@ -106,43 +180,812 @@ pub fn try_set_handler<F>(user_handler: F) -> Result<(), Error>
 where
     F: FnMut() + 'static + Send,
 {
-    init_and_set_handler(user_handler, false)
+    init_and_set_handler(user_handler, ConflictPolicy::ErrorOnCustomOnly).map(HandlerGuard::detach)
 }
 
-fn init_and_set_handler<F>(user_handler: F, overwrite: bool) -> Result<(), Error>
+/// The same as ctrlc::set_handler, but the handler receives information about the signal.
+///
+/// See [`SignalInfo`](struct.SignalInfo.html) for what is available on each platform. On Windows,
+/// only the type of the most recent event is kept, so when several events arrive before the
+/// handler runs, every run reports that one.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler_with_info(|info| match info.sender_pid {
+///     Some(pid) => println!("{} sent by {}", info.signal, pid),
+///     None => println!("{}", info.signal),
+/// })
+/// .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn set_handler_with_info<F>(user_handler: F) -> Result<(), Error>
+where
+    F: FnMut(SignalInfo) + 'static + Send,
+{
+    init_and_set_info_handler_with(
+        &HandlerBuilder::new(),
+        user_handler,
+        ConflictPolicy::Overwrite,
+    )
+    .map(HandlerGuard::detach)
+}
+
+/// How to treat signal handlers that exist when registering a handler, see
+/// [`set_handler_with_conflict_policy()`](fn.set_handler_with_conflict_policy.html).
+///
+/// Only applies on Unix. On Windows, multiple handler routines are allowed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Error unless the signals have the default disposition (`SIG_DFL`).
+    ErrorOnAny,
+    /// Error if the signals have a handler installed. Signals that are ignored (`SIG_IGN`) are
+    /// taken over, e.g. when the parent process ignored `SIGINT` before executing this one. This
+    /// is the policy of [`try_set_handler()`](fn.try_set_handler.html).
+    #[default]
+    ErrorOnCustomOnly,
+    /// Overwrite any existing handler. This is the policy of
+    /// [`set_handler()`](fn.set_handler.html).
+    Overwrite,
+}
+
+/// The same as ctrlc::set_handler but handles existing signal handlers according to `policy`.
+///
+/// # Example
+/// ```no_run
+/// use ctrlc::ConflictPolicy;
+///
+/// ctrlc::set_handler_with_conflict_policy(|| println!("Hello world!"), ConflictPolicy::ErrorOnAny)
+///     .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return [`Error::MultipleHandlers`] if a handler exists that conflicts with `policy`, or
+/// another error if a system error occurred while setting the handler.
+pub fn set_handler_with_conflict_policy<F>(
+    user_handler: F,
+    policy: ConflictPolicy,
+) -> Result<(), Error>
 where
     F: FnMut() + 'static + Send,
 {
+    init_and_set_handler(user_handler, policy).map(HandlerGuard::detach)
+}
+
+/// The same as ctrlc::set_handler but the handler is a function that receives a mutable
+/// reference to `data`.
+///
+/// `data` is moved to the signal handling thread and lives as long as the handler.
+///
+/// # Example
+/// ```no_run
+/// fn count(n: &mut u32) {
+///     *n += 1;
+///     println!("Ctrl-C number {}", n);
+/// }
+///
+/// ctrlc::set_handler_with_data(0, count).expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn set_handler_with_data<T>(mut data: T, handler: fn(&mut T)) -> Result<(), Error>
+where
+    T: Send + 'static,
+{
+    set_handler(move || handler(&mut data))
+}
+
+/// The same as ctrlc::set_handler but the handler is shared behind an `Arc<Mutex<..>>`, e.g. when
+/// it is also stored elsewhere.
+///
+/// The mutex is locked for every run of the handler. A panic in the handler is caught rather than
+/// stopping the signal handling thread, and the handler is still called if the mutex has been
+/// poisoned by a panic elsewhere.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::{Arc, Mutex};
+///
+/// let handler: Arc<Mutex<dyn FnMut() + Send>> = Arc::new(Mutex::new(|| println!("Hello world!")));
+/// ctrlc::set_handler_dyn(Arc::clone(&handler)).expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn set_handler_dyn(handler: Arc<Mutex<dyn FnMut() + Send>>) -> Result<(), Error> {
+    set_handler(move || {
+        let mut handler = handler.lock().unwrap_or_else(PoisonError::into_inner);
+        let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| (*handler)()));
+    })
+}
+
+/// The same as ctrlc::set_handler but sends the payload of a panic in the handler through
+/// `panic_tx`.
+///
+/// The signal handling thread stops after a panic as with ctrlc::set_handler, but the payload can
+/// be received, e.g. to resume the panic with `std::panic::resume_unwind()` on the main thread.
+///
+/// # Example
+/// ```no_run
+/// let (panic_tx, panic_rx) = std::sync::mpsc::sync_channel(1);
+/// ctrlc::set_handler_with_panic_propagation(|| panic!("Hello world!"), panic_tx)
+///     .expect("Error setting Ctrl-C handler");
+/// if let Ok(payload) = panic_rx.recv() {
+///     std::panic::resume_unwind(payload);
+/// }
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn set_handler_with_panic_propagation<F>(
+    user_handler: F,
+    panic_tx: mpsc::SyncSender<Box<dyn std::any::Any + Send>>,
+) -> Result<(), Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    HandlerBuilder::new()
+        .on_panic(move |payload| {
+            let _ = panic_tx.send(payload);
+        })
+        .set_handler(user_handler)
+}
+
+/// The same as ctrlc::set_handler but exits the process if the handler runs for longer than
+/// `max_handler_duration`.
+///
+/// A watchdog thread is started alongside the signal handling thread. Each time the handler is
+/// invoked, the watchdog waits for it to return and calls `std::process::exit(1)` if it has not
+/// done so within `max_handler_duration`. This guarantees that the process terminates even if
+/// the cleanup performed by the handler hangs, e.g. on a lock held by a deadlocked thread.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// ctrlc::set_handler_with_timeout(|| println!("Cleaning up..."), Duration::from_secs(5))
+///     .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn set_handler_with_timeout<F>(
+    mut user_handler: F,
+    max_handler_duration: Duration,
+) -> Result<(), Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    // The handler sends one message when it starts and one when it returns. If the handler is
+    // dropped (the handler panicked) the watchdog exits.
+    let (tx, rx) = mpsc::channel::<()>();
+
+    let guard = init_and_set_handler(
+        move || {
+            let _ = tx.send(());
+            user_handler();
+            let _ = tx.send(());
+        },
+        ConflictPolicy::Overwrite,
+    )?;
+
+    // Spawned after the handler has been set so that it inherits the signal mask of the
+    // signalfd backend. The guard removes the handler again if the spawn fails.
+    thread::Builder::new()
+        .name("ctrl-c-watchdog".into())
+        .spawn(move || {
+            while rx.recv().is_ok() {
+                match rx.recv_timeout(max_handler_duration) {
+                    Ok(()) => {}
+                    Err(RecvTimeoutError::Timeout) => process::exit(1),
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        })
+        .map_err(Error::System)?;
+
+    guard.detach();
+    Ok(())
+}
+
+/// The same as ctrlc::set_handler but runs the handler inside `span`.
+///
+/// Only available with the `tracing` feature. The signal handling thread has no span of its own,
+/// so events recorded by the handler would otherwise not be part of the application's trace.
+/// The span is entered before each invocation of the handler and exited after it returns.
+///
+/// # Example
+/// ```no_run
+/// let span = tracing::info_span!("shutdown");
+/// ctrlc::set_handler_with_span(|| tracing::info!("Ctrl-C received"), span)
+///     .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+#[cfg(feature = "tracing")]
+pub fn set_handler_with_span<F>(mut user_handler: F, span: tracing::Span) -> Result<(), Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    set_handler(move || span.in_scope(&mut user_handler))
+}
+
+/// Exit code of a process terminated by Ctrl-C, by the shell convention of 128 plus the number of
+/// `SIGINT`.
+pub const SIGINT_EXIT_CODE: i32 = 130;
+
+/// What to do when Ctrl-C is received repeatedly, see
+/// [`set_handler_with_escalation()`](fn.set_handler_with_escalation.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscalationPolicy {
+    /// Exit the process with the given exit code when the given number of signals has been
+    /// received. The handler runs for all signals before that.
+    ForceExitAfter(u32, i32),
+    /// Run the handler for every signal.
+    Ignore,
+}
+
+/// The same as ctrlc::set_handler but exits the process on repeated Ctrl-C according to
+/// `escalation`.
+///
+/// A common pattern is to shut down gracefully on the first Ctrl-C and to exit immediately on
+/// the second one, for when the graceful shutdown hangs. With
+/// `EscalationPolicy::ForceExitAfter(2, SIGINT_EXIT_CODE)` the handler runs on the first Ctrl-C
+/// and the process exits with code 130 on the second.
+///
+/// The handler runs in a separate thread from the one receiving the signals, so the signals are
+/// counted even while the handler is still running.
+///
+/// # Example
+/// ```no_run
+/// use ctrlc::{EscalationPolicy, SIGINT_EXIT_CODE};
+///
+/// ctrlc::set_handler_with_escalation(
+///     || println!("Shutting down, press Ctrl-C again to force exit"),
+///     EscalationPolicy::ForceExitAfter(2, SIGINT_EXIT_CODE),
+/// )
+/// .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn set_handler_with_escalation<F>(
+    mut user_handler: F,
+    escalation: EscalationPolicy,
+) -> Result<(), Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    let (max_count, exit_code) = match escalation {
+        EscalationPolicy::ForceExitAfter(max_count, exit_code) => (max_count, exit_code),
+        EscalationPolicy::Ignore => return set_handler(user_handler),
+    };
+
+    let (tx, rx) = mpsc::channel::<()>();
+    let mut count = 0u32;
+
+    let guard = init_and_set_handler(
+        move || {
+            count = count.saturating_add(1);
+            if count >= max_count {
+                process::exit(exit_code);
+            }
+            let _ = tx.send(());
+        },
+        ConflictPolicy::Overwrite,
+    )?;
+
+    // Spawned after the handler has been set so that it inherits the signal mask of the
+    // signalfd backend. The guard removes the handler again if the spawn fails.
+    thread::Builder::new()
+        .name("ctrl-c-handler".into())
+        .spawn(move || {
+            while rx.recv().is_ok() {
+                user_handler();
+            }
+        })
+        .map_err(Error::System)?;
+
+    guard.detach();
+    Ok(())
+}
+
+/// Print `first_message` to stderr on the first Ctrl-C and exit with
+/// [`SIGINT_EXIT_CODE`](constant.SIGINT_EXIT_CODE.html) on the second.
+///
+/// The shorthand for [`set_handler_with_escalation()`](fn.set_handler_with_escalation.html) with
+/// `EscalationPolicy::ForceExitAfter(2, SIGINT_EXIT_CODE)`. Use
+/// [`install_double_ctrl_c_exit_with()`](fn.install_double_ctrl_c_exit_with.html) to also start
+/// the shutdown of the program on the first Ctrl-C.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::install_double_ctrl_c_exit("Shutting down, press Ctrl-C again to force exit")
+///     .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn install_double_ctrl_c_exit(first_message: &'static str) -> Result<(), Error> {
+    install_double_ctrl_c_exit_with(first_message, || {})
+}
+
+/// The same as ctrlc::install_double_ctrl_c_exit but also runs `on_first` after printing the
+/// message on the first Ctrl-C.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::mpsc;
+///
+/// let (tx, rx) = mpsc::channel();
+/// ctrlc::install_double_ctrl_c_exit_with("Shutting down...", move || {
+///     let _ = tx.send(());
+/// })
+/// .expect("Error setting Ctrl-C handler");
+/// rx.recv().unwrap();
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn install_double_ctrl_c_exit_with<F>(
+    first_message: &'static str,
+    mut on_first: F,
+) -> Result<(), Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    set_handler_with_escalation(
+        move || {
+            eprintln!("{}", first_message);
+            on_first();
+        },
+        EscalationPolicy::ForceExitAfter(2, SIGINT_EXIT_CODE),
+    )
+}
+
+/// Set the stack size of the signal handling thread, in bytes.
+///
+/// By default the thread gets the default stack size of Rust threads, usually 2 MiB. Only
+/// affects handlers set after this call, so it should be called before
+/// [`set_handler()`](fn.set_handler.html).
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler_stack_size(64 * 1024).expect("Invalid stack size");
+/// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if `size` is less than `SIGSTKSZ` on Unix.
+pub fn set_handler_stack_size(size: usize) -> Result<(), Error> {
+    check_stack_size(size)?;
+    HANDLER_STACK_SIZE.store(size, Ordering::Relaxed);
+    Ok(())
+}
+
+// MIN_STACK_SIZE is 0 on Windows and some Unix platforms.
+#[allow(clippy::absurd_extreme_comparisons)]
+fn check_stack_size(size: usize) -> Result<(), Error> {
+    if size < platform::MIN_STACK_SIZE {
+        return Err(Error::System(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "stack size must be at least {} bytes",
+                platform::MIN_STACK_SIZE
+            ),
+        )));
+    }
+
+    Ok(())
+}
+
+/// Register a scoped signal handler for Ctrl-C.
+///
+/// The same as ctrlc::set_handler, but the handler is removed when the returned guard is
+/// dropped. See [`HandlerGuard`](struct.HandlerGuard.html).
+///
+/// # Example
+/// ```no_run
+/// let guard = ctrlc::set_scoped_handler(|| println!("Hello world!"))
+///     .expect("Error setting Ctrl-C handler");
+/// // Ctrl-C is handled until the guard is dropped
+/// drop(guard);
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn set_scoped_handler<F>(user_handler: F) -> Result<HandlerGuard, Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    init_and_set_handler(user_handler, ConflictPolicy::Overwrite)
+}
+
+/// The same as ctrlc::set_scoped_handler but errors if a handler already exists for the
+/// signal(s).
+///
+/// # Errors
+/// Will return an error if another handler exists or if a system error occurred while setting the
+/// handler.
+pub fn try_set_scoped_handler<F>(user_handler: F) -> Result<HandlerGuard, Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    init_and_set_handler(user_handler, ConflictPolicy::ErrorOnCustomOnly)
+}
+
+/// Remove the registered handler, if any.
+///
+/// Waits for the signal handling thread to exit, unless called from the handler itself. On Unix
+/// the signal dispositions are reset to `SIG_DFL`, on Windows the handler routine is
+/// unregistered. A new handler can be set afterwards. With the `signalfd` feature, signals not
+/// read yet are discarded, and the handled signals are unblocked again if called from the thread
+/// that set the handler.
+///
+/// Returns `Ok(())` if no handler is registered, so it can be called unconditionally, e.g. in the
+/// teardown of tests that each set a handler of their own.
+///
+/// # Errors
+/// Will return an error if a system error occurred while removing the handler.
+pub fn remove_all_handlers() -> Result<(), Error> {
+    remove_handler(None)
+}
+
+/// Whether a handler is registered.
+///
+/// Returns `false` again once the handler has been removed, e.g. with
+/// [`remove_all_handlers()`](fn.remove_all_handlers.html) or by dropping its
+/// [`HandlerGuard`](struct.HandlerGuard.html).
+#[must_use = "checking whether a handler is registered has no side effects"]
+pub fn is_handler_registered() -> bool {
+    INIT.load(Ordering::Acquire)
+}
+
+/// Name of the signal handling thread, if a handler is registered.
+///
+/// The thread is named `ctrl-c` unless another name was set with
+/// [`HandlerBuilder::name()`](struct.HandlerBuilder.html#method.name). Custom names are kept for
+/// the rest of the program, once per distinct name, so that they can be returned as
+/// `&'static str`.
+#[must_use = "looking up the thread name has no side effects"]
+pub fn handler_thread_name() -> Option<&'static str> {
+    let _guard = INIT_LOCK.lock().unwrap();
+
+    if !INIT.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    HANDLER_THREAD.lock().unwrap().as_ref().map(|h| h.name)
+}
+
+fn intern_thread_name(name: &str) -> &'static str {
+    let mut names = THREAD_NAMES.lock().unwrap();
+    match names.iter().find(|n| **n == name) {
+        Some(n) => n,
+        None => {
+            let n: &'static str = Box::leak(name.into());
+            names.push(n);
+            n
+        }
+    }
+}
+
+/// Block the calling thread until Ctrl-C is received.
+///
+/// A scoped handler is registered for the duration of the call and removed before returning,
+/// see [`set_scoped_handler()`](fn.set_scoped_handler.html). This blocks the calling thread, do
+/// not call it from async context, use [`ctrl_c()`](fn.ctrl_c.html) there instead.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::block_until_ctrl_c().expect("Error setting Ctrl-C handler");
+/// println!("Got Ctrl-C");
+/// ```
+///
+/// # Errors
+/// Will return [`Error::MultipleHandlers`] if a handler is already registered, or an error if a
+/// system error occurred while setting the handler.
+pub fn block_until_ctrl_c() -> Result<(), Error> {
+    let (tx, rx) = mpsc::channel();
+    let _guard = set_scoped_handler(move || {
+        let _ = tx.send(());
+    })?;
+
+    // The sender lives in the handler, which is not removed before the guard is dropped.
+    rx.recv().unwrap();
+    Ok(())
+}
+
+/// Register a signal handler for Ctrl-C that runs only once.
+///
+/// The handler is removed after its first run, as with
+/// [`remove_all_handlers()`](fn.remove_all_handlers.html), and its return value is available from
+/// [`OnceHandlerHandle::join()`](struct.OnceHandlerHandle.html#method.join). Unlike the handle of
+/// a scoped handler, dropping the returned handle does not remove the handler.
+///
+/// # Example
+/// ```no_run
+/// let handle = ctrlc::set_handler_once(|| "Hello world!").expect("Error setting Ctrl-C handler");
+/// println!("{:?}", handle.join().unwrap());
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn set_handler_once<F, T>(user_handler: F) -> Result<OnceHandlerHandle<T>, Error>
+where
+    F: FnOnce() -> T + 'static + Send,
+    T: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    let mut user_handler = Some(user_handler);
+
+    init_and_set_handler(
+        move || {
+            if let Some(user_handler) = user_handler.take() {
+                let result = panic::catch_unwind(panic::AssertUnwindSafe(user_handler));
+                // Runs on the handler thread, which is the one of this handler. Removed before
+                // sending the result, so that it is gone once join() returns.
+                let _ = remove_handler(None);
+                let _ = tx.send(result);
+            }
+        },
+        ConflictPolicy::Overwrite,
+    )
+    .map(HandlerGuard::detach)?;
+
+    Ok(OnceHandlerHandle { rx })
+}
+
+/// Handle for a handler set with [`set_handler_once()`](fn.set_handler_once.html).
+#[derive(Debug)]
+#[must_use = "the return value of the handler is only available through the handle"]
+pub struct OnceHandlerHandle<T> {
+    rx: mpsc::Receiver<thread::Result<T>>,
+}
+
+impl<T> OnceHandlerHandle<T> {
+    /// Wait for the handler to run and return its return value.
+    ///
+    /// Returns `Ok(None)` if the handler was removed before Ctrl-C was received. Blocks until
+    /// either happens. The handler is no longer registered once this returns.
+    ///
+    /// # Errors
+    /// Will return the panic payload if the handler panicked.
+    pub fn join(self) -> thread::Result<Option<T>> {
+        match self.rx.recv() {
+            Ok(result) => result.map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Guard for a handler set with [`set_scoped_handler()`](fn.set_scoped_handler.html) or
+/// [`try_set_scoped_handler()`](fn.try_set_scoped_handler.html).
+///
+/// The handler is removed, as with [`remove_all_handlers()`](fn.remove_all_handlers.html), when
+/// the guard is dropped. If the handler has already been removed, dropping the guard has no
+/// effect, even if another handler has been set since.
+#[derive(Debug)]
+#[must_use = "the handler is removed when the guard is dropped"]
+pub struct HandlerGuard {
+    generation: u64,
+}
+
+impl HandlerGuard {
+    /// Keep the handler registered for the rest of the program.
+    pub fn detach(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for HandlerGuard {
+    fn drop(&mut self) {
+        // Can't really handle errors in any meaningful way.
+        let _ = remove_handler(Some(self.generation));
+    }
+}
+
+struct HandlerThread {
+    generation: u64,
+    name: &'static str,
+    stop: Arc<AtomicBool>,
+    thread: thread::JoinHandle<()>,
+}
+
+fn init_and_set_handler<F>(user_handler: F, policy: ConflictPolicy) -> Result<HandlerGuard, Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    init_and_set_handler_with(&HandlerBuilder::new(), user_handler, policy)
+}
+
+fn init_and_set_handler_with<F>(
+    builder: &HandlerBuilder,
+    mut user_handler: F,
+    policy: ConflictPolicy,
+) -> Result<HandlerGuard, Error>
+where
+    F: FnMut() + 'static + Send,
+{
+    init_and_set_info_handler_with(builder, move |_| user_handler(), policy)
+}
+
+fn init_and_set_info_handler_with<F>(
+    builder: &HandlerBuilder,
+    user_handler: F,
+    policy: ConflictPolicy,
+) -> Result<HandlerGuard, Error>
+where
+    F: FnMut(SignalInfo) + 'static + Send,
+{
+    if let Some(size) = builder.stack_size {
+        check_stack_size(size)?;
+    }
+
     if !INIT.load(Ordering::Acquire) {
         let _guard = INIT_LOCK.lock().unwrap();
 
         if !INIT.load(Ordering::Relaxed) {
-            set_handler_inner(user_handler, overwrite)?;
+            let generation = set_handler_inner(builder, user_handler, policy)?;
             INIT.store(true, Ordering::Release);
-            return Ok(());
+            return Ok(HandlerGuard { generation });
         }
     }
 
-    Err(Error::MultipleHandlers)
+    // The handler of ctrlc conflicts with every signal it handles. SIGINT is always one of them
+    // and is the first checked when the handler is set.
+    Err(Error::MultipleHandlers(platform::CTRL_C_SIGNAL))
 }
 
-fn set_handler_inner<F>(mut user_handler: F, overwrite: bool) -> Result<(), Error>
+fn set_handler_inner<F>(
+    builder: &HandlerBuilder,
+    mut user_handler: F,
+    policy: ConflictPolicy,
+) -> Result<u64, Error>
 where
-    F: FnMut() + 'static + Send,
+    F: FnMut(SignalInfo) + 'static + Send,
 {
     unsafe {
-        platform::init_os_handler(overwrite)?;
+        platform::init_os_handler(policy)?;
     }
 
-    thread::Builder::new()
-        .name("ctrl-c".into())
-        .spawn(move || loop {
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = Arc::clone(&stop);
+
+    let name = match builder.name {
+        Some(ref name) => intern_thread_name(name),
+        None => "ctrl-c",
+    };
+    let mut thread_builder = thread::Builder::new().name(name.into());
+    match builder
+        .stack_size
+        .unwrap_or_else(|| HANDLER_STACK_SIZE.load(Ordering::Relaxed))
+    {
+        0 => {}
+        size => thread_builder = thread_builder.stack_size(size),
+    }
+
+    let on_panic = builder.on_panic.clone();
+    let coalesce = builder.coalesce;
+    #[cfg(unix)]
+    let sigaltstack = builder.sigaltstack;
+    let (init_tx, init_rx) = mpsc::channel::<Result<(), Error>>();
+    let thread = thread_builder.spawn(move || {
+        platform::init_handler_thread().expect("Critical system error while waiting for Ctrl-C");
+        // Alternate signal stacks are per-thread, so it has to be installed from here.
+        #[cfg(unix)]
+        let _sigaltstack = match sigaltstack.map(unix::configure_sigaltstack) {
+            Some(Err(e)) => {
+                let _ = init_tx.send(Err(e));
+                return;
+            }
+            guard => guard,
+        };
+        let _ = init_tx.send(Ok(()));
+        let mut last_run: Option<Instant> = None;
+        while !thread_stop.load(Ordering::Acquire) {
+            let result = unsafe { platform::block_ctrl_c() };
+            if thread_stop.load(Ordering::Acquire) {
+                break;
+            }
+            let info = result.expect("Critical system error while waiting for Ctrl-C");
+            *SIGNALS_RECEIVED.lock().unwrap() += 1;
+            SIGNAL_RECEIVED.notify_all();
+            if let Some(min_interval) = coalesce {
+                let now = Instant::now();
+                if last_run.map_or(false, |last| now.duration_since(last) < min_interval) {
+                    continue;
+                }
+                last_run = Some(now);
+            }
+            match on_panic {
+                Some(ref on_panic) => {
+                    let result =
+                        panic::catch_unwind(panic::AssertUnwindSafe(|| user_handler(info)));
+                    if let Err(payload) = result {
+                        on_panic(payload);
+                        break;
+                    }
+                }
+                None => user_handler(info),
+            }
+        }
+    });
+
+    let thread = match thread {
+        Ok(thread) => thread,
+        Err(e) => {
             unsafe {
-                platform::block_ctrl_c().expect("Critical system error while waiting for Ctrl-C");
+                let _ = platform::deinit_os_handler();
             }
-            user_handler();
-        })
-        .map_err(Error::System)?;
+            return Err(Error::System(e));
+        }
+    };
+    if let Ok(Err(e)) = init_rx.recv() {
+        let _ = thread.join();
+        unsafe {
+            let _ = platform::deinit_os_handler();
+        }
+        return Err(e);
+    }
+
+    let generation = GENERATION.fetch_add(1, Ordering::Relaxed);
+    let stale = HANDLER_THREAD.lock().unwrap().replace(HandlerThread {
+        generation,
+        name,
+        stop,
+        thread,
+    });
+    // Left behind by a handler unregistered in the child after fork(2). Its thread only exists
+    // in the parent, so the handle must not be joined or detached.
+    mem::forget(stale);
+
+    Ok(generation)
+}
+
+fn remove_handler(generation: Option<u64>) -> Result<(), Error> {
+    let handler_thread = {
+        let _guard = INIT_LOCK.lock().unwrap();
+
+        // Already unregistered, possibly in the child after fork(2).
+        if !INIT.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let mut handler_thread = HANDLER_THREAD.lock().unwrap();
+        match *handler_thread {
+            Some(ref h) if generation.map_or(true, |g| g == h.generation) => {}
+            // Not registered, or already being removed by another thread.
+            _ => return Ok(()),
+        }
+        let handler_thread = handler_thread.take().unwrap();
+        handler_thread.stop.store(true, Ordering::Release);
+        handler_thread
+    };
+
+    // The locks are released while waiting for the thread, as a running handler may take them
+    // too, e.g. to remove itself. INIT stays set until the thread has exited, so no other handler
+    // can be registered while it may still be waiting for signals.
+    //
+    // The handler may remove itself, in which case the thread exits once the handler returns.
+    if handler_thread.thread.thread().id() != thread::current().id() {
+        unsafe {
+            platform::wake_ctrl_c(&handler_thread.thread)?;
+        }
+        let _ = handler_thread.thread.join();
+    }
+
+    let _guard = INIT_LOCK.lock().unwrap();
+    unsafe {
+        platform::deinit_os_handler()?;
+    }
 
+    INIT.store(false, Ordering::Release);
+    // Wake up threads waiting for a signal, so that they notice there is no handler anymore.
+    let _received = SIGNALS_RECEIVED.lock().unwrap();
+    SIGNAL_RECEIVED.notify_all();
     Ok(())
 }
@@ -7,11 +7,27 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
 use crate::error::Error as CtrlcError;
+use crate::signal::{SignalInfo, SignalType};
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+use crate::ConflictPolicy;
+use nix::libc;
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
 use nix::unistd;
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
 use std::os::fd::BorrowedFd;
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
 use std::os::fd::IntoRawFd;
 use std::os::unix::io::RawFd;
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(all(feature = "signalfd", any(target_os = "linux", target_os = "android")))]
+mod signalfd;
+#[cfg(all(feature = "signalfd", any(target_os = "linux", target_os = "android")))]
+pub use self::signalfd::*;
 
 static mut PIPE: (RawFd, RawFd) = (-1, -1);
 
@@ -21,11 +37,211 @@ pub type Error = nix::Error;
 /// Platform specific signal type
 pub type Signal = nix::sys::signal::Signal;
 
-extern "C" fn os_handler(_: nix::libc::c_int) {
-    // Assuming this always succeeds. Can't really handle errors in any meaningful way.
-    unsafe {
+/// Minimum stack size of the signal handling thread.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "illumos",
+    target_os = "solaris",
+    target_os = "haiku",
+    target_os = "aix",
+))]
+pub const MIN_STACK_SIZE: usize = nix::libc::SIGSTKSZ;
+/// Minimum stack size of the signal handling thread.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "illumos",
+    target_os = "solaris",
+    target_os = "haiku",
+    target_os = "aix",
+)))]
+pub const MIN_STACK_SIZE: usize = 0;
+
+/// Signal written for `SignalType::Ctrlc`.
+pub const CTRL_C_SIGNAL: Signal = Signal::SIGINT;
+
+/// Signal written for `SignalType::Termination`.
+pub const TERMINATION_SIGNAL: Signal = Signal::SIGTERM;
+
+/// Get the name of a signal.
+pub fn signal_name(signal: Signal) -> Option<&'static str> {
+    Some(signal.as_str())
+}
+
+/// Get a signal from its name, matched case-insensitively.
+pub fn signal_from_name(name: &str) -> Option<Signal> {
+    Signal::iterator().find(|signal| signal.as_str().eq_ignore_ascii_case(name))
+}
+
+/// Get a signal from its number.
+pub fn signal_from_raw(num: i32) -> Option<Signal> {
+    Signal::try_from(num).ok()
+}
+
+/// Iterate over all signals.
+pub fn signal_iterator() -> impl Iterator<Item = Signal> {
+    Signal::iterator()
+}
+
+/// Restore the default disposition of `signal`.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[inline]
+pub unsafe fn revert_sighandler_to_default(signal: Signal) -> Result<(), Error> {
+    use nix::sys::signal;
+
+    let default = signal::SigAction::new(
+        signal::SigHandler::SigDfl,
+        signal::SaFlags::empty(),
+        signal::SigSet::empty(),
+    );
+    signal::sigaction(signal, &default).map(drop)
+}
+
+/// Get the platform specific signals a `SignalType` corresponds to.
+pub fn platform_signals(signal: &crate::SignalType) -> Vec<Signal> {
+    use crate::SignalType;
+
+    match *signal {
+        SignalType::Ctrlc => vec![Signal::SIGINT],
+        SignalType::Termination => vec![Signal::SIGTERM],
+        SignalType::Hangup => vec![Signal::SIGHUP],
+        SignalType::Quit => vec![Signal::SIGQUIT],
+        SignalType::WindowChange => vec![Signal::SIGWINCH],
+        SignalType::UserDefined1 => vec![Signal::SIGUSR1],
+        SignalType::UserDefined2 => vec![Signal::SIGUSR2],
+        SignalType::Other(signal) => vec![signal],
+    }
+}
+
+/// Whether `signal` is generated by the terminal for the foreground process group.
+pub fn is_terminal_signal(signal: Signal) -> bool {
+    matches!(signal, Signal::SIGINT | Signal::SIGQUIT | Signal::SIGTSTP)
+}
+
+/// Unregisters the handler in the child process after `fork(2)`.
+///
+/// Only the thread calling `fork()` exists in the child, so the handling thread does not and the
+/// signals would end up in the parent's pipe. Restores the default disposition of the handled
+/// signals and closes the child's copy of the pipe, so that the child can register a handler of
+/// its own. Only calls async-signal-safe functions and takes no locks.
+extern "C" fn atfork_child() {
+    if crate::INIT.load(Ordering::Relaxed) {
+        unsafe {
+            let _ = deinit_os_handler();
+        }
+        crate::INIT.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Registers [`atfork_child()`](fn.atfork_child.html) with `pthread_atfork(3)`, once.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+unsafe fn register_atfork() -> Result<(), Error> {
+    static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+    // Only called from init_os_handler(), which runs under INIT_LOCK.
+    if !REGISTERED.load(Ordering::Relaxed) {
+        match nix::libc::pthread_atfork(None, None, Some(atfork_child)) {
+            0 => REGISTERED.store(true, Ordering::Relaxed),
+            e => return Err(Error::from_raw(e)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Build the `SignalInfo` for `signal` from the `si_pid` and `si_uid` of the signal, which are zero
+/// unless it was sent by a process.
+fn signal_info(signal: libc::c_int, pid: u32, uid: u32) -> SignalInfo {
+    let signal = match Signal::try_from(signal) {
+        Ok(signal) => SignalType::from_platform_signal(signal),
+        Err(_) => SignalType::Ctrlc,
+    };
+    let sent = pid != 0;
+
+    SignalInfo {
+        signal,
+        sender_pid: if sent { Some(pid) } else { None },
+        sender_uid: if sent { Some(uid) } else { None },
+    }
+}
+
+/// A received signal as written to the pipe. Writes of up to `PIPE_BUF` bytes are atomic, so
+/// records are never split or interleaved.
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Record {
+    signal: libc::c_int,
+    pid: libc::pid_t,
+    uid: libc::uid_t,
+}
+
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+impl Record {
+    /// Record without a sender, for wake-ups and injected signals.
+    fn new(signal: libc::c_int) -> Record {
+        Record {
+            signal,
+            pid: 0,
+            uid: 0,
+        }
+    }
+
+    unsafe fn write(&self) -> Result<usize, Error> {
         let fd = BorrowedFd::borrow_raw(PIPE.1);
-        let _ = unistd::write(fd, &[0u8]);
+        let bytes = std::slice::from_raw_parts(
+            self as *const Record as *const u8,
+            std::mem::size_of::<Record>(),
+        );
+        unistd::write(fd, bytes)
+    }
+}
+
+/// Number of signals the OS handler could not pass on because the pipe was full.
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+pub static DROPPED_SIGNALS: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether `handler` is the OS handler installed by [`init_os_handler()`](fn.init_os_handler.html).
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+pub fn is_os_handler(handler: libc::sighandler_t) -> bool {
+    handler == os_handler as *const () as libc::sighandler_t
+}
+
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+extern "C" fn os_handler(signal: libc::c_int, info: *mut libc::siginfo_t, _: *mut libc::c_void) {
+    // The write end is non-blocking, so the only error expected is EAGAIN when the handler thread
+    // falls behind. The signal is dropped then, nothing else can be done in a signal handler.
+    unsafe {
+        let record = match info.as_ref() {
+            Some(info) => Record {
+                signal,
+                pid: info.si_pid(),
+                uid: info.si_uid(),
+            },
+            None => Record::new(signal),
+        };
+        if record.write() == Err(nix::errno::Errno::EAGAIN) {
+            DROPPED_SIGNALS.fetch_add(1, Ordering::Relaxed);
+        }
     }
 }
 
@@ -69,6 +285,7 @@ fn pipe2(flags: nix::fcntl::OFlag) -> nix::Result<(RawFd, RawFd)> {
 }
 
 #[inline]
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
 #[cfg(not(any(
     target_os = "ios",
     target_os = "macos",
@@ -81,6 +298,31 @@ fn pipe2(flags: nix::fcntl::OFlag) -> nix::Result<(RawFd, RawFd)> {
     Ok((pipe.0.into_raw_fd(), pipe.1.into_raw_fd()))
 }
 
+/// Sends `SIGINT` to the process.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[cfg(feature = "test-utils")]
+#[inline]
+pub unsafe fn raise_ctrl_c() -> Result<(), Error> {
+    use nix::sys::signal;
+
+    signal::kill(nix::unistd::Pid::this(), signal::Signal::SIGINT)
+}
+
+/// Whether an existing handler conflicts with registering one under `policy`.
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+fn is_conflict(policy: ConflictPolicy, old: nix::sys::signal::SigHandler) -> bool {
+    use nix::sys::signal::SigHandler;
+
+    match policy {
+        ConflictPolicy::ErrorOnAny => old != SigHandler::SigDfl,
+        ConflictPolicy::ErrorOnCustomOnly => old != SigHandler::SigDfl && old != SigHandler::SigIgn,
+        ConflictPolicy::Overwrite => false,
+    }
+}
+
 /// Register os signal handler.
 ///
 /// Must be called before calling [`block_ctrl_c()`](fn.block_ctrl_c.html)
@@ -89,14 +331,16 @@ fn pipe2(flags: nix::fcntl::OFlag) -> nix::Result<(RawFd, RawFd)> {
 /// # Errors
 /// Will return an error if a system error occurred.
 ///
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
 #[inline]
-pub unsafe fn init_os_handler(overwrite: bool) -> Result<(), Error> {
+pub unsafe fn init_os_handler(policy: ConflictPolicy) -> Result<(), CtrlcError> {
     use nix::fcntl;
     use nix::sys::signal;
 
+    register_atfork()?;
     PIPE = pipe2(fcntl::OFlag::O_CLOEXEC)?;
 
-    let close_pipe = |e: nix::Error| -> Error {
+    let close_pipe = |e: CtrlcError| -> CtrlcError {
         // Try to close the pipes. close() should not fail,
         // but if it does, there isn't much we can do
         let _ = unistd::close(PIPE.1);
@@ -106,28 +350,33 @@ pub unsafe fn init_os_handler(overwrite: bool) -> Result<(), Error> {
 
     // Make sure we never block on write in the os handler.
     if let Err(e) = fcntl::fcntl(PIPE.1, fcntl::FcntlArg::F_SETFL(fcntl::OFlag::O_NONBLOCK)) {
-        return Err(close_pipe(e));
+        return Err(close_pipe(e.into()));
     }
 
-    let handler = signal::SigHandler::Handler(os_handler);
+    let handler = signal::SigHandler::SigAction(os_handler);
+    // SA_ONSTACK runs the handler on the alternate signal stack of the thread if one has been
+    // configured, and has no effect otherwise.
     #[cfg(not(target_os = "nto"))]
     let new_action = signal::SigAction::new(
         handler,
-        signal::SaFlags::SA_RESTART,
+        signal::SaFlags::SA_RESTART | signal::SaFlags::SA_ONSTACK,
         signal::SigSet::empty(),
     );
     // SA_RESTART is not supported on QNX Neutrino 7.1 and before
     #[cfg(target_os = "nto")]
-    let new_action =
-        signal::SigAction::new(handler, signal::SaFlags::empty(), signal::SigSet::empty());
+    let new_action = signal::SigAction::new(
+        handler,
+        signal::SaFlags::SA_ONSTACK,
+        signal::SigSet::empty(),
+    );
 
     let sigint_old = match signal::sigaction(signal::Signal::SIGINT, &new_action) {
         Ok(old) => old,
-        Err(e) => return Err(close_pipe(e)),
+        Err(e) => return Err(close_pipe(e.into())),
     };
-    if !overwrite && sigint_old.handler() != signal::SigHandler::SigDfl {
+    if is_conflict(policy, sigint_old.handler()) {
         signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
-        return Err(close_pipe(nix::Error::EEXIST));
+        return Err(close_pipe(CtrlcError::MultipleHandlers(Signal::SIGINT)));
     }
 
     #[cfg(feature = "termination")]
@@ -136,33 +385,74 @@ pub unsafe fn init_os_handler(overwrite: bool) -> Result<(), Error> {
             Ok(old) => old,
             Err(e) => {
                 signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
-                return Err(close_pipe(e));
+                return Err(close_pipe(e.into()));
             }
         };
-        if !overwrite && sigterm_old.handler() != signal::SigHandler::SigDfl {
+        if is_conflict(policy, sigterm_old.handler()) {
             signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
             signal::sigaction(signal::Signal::SIGTERM, &sigterm_old).unwrap();
-            return Err(close_pipe(nix::Error::EEXIST));
+            return Err(close_pipe(CtrlcError::MultipleHandlers(Signal::SIGTERM)));
         }
         let sighup_old = match signal::sigaction(signal::Signal::SIGHUP, &new_action) {
             Ok(old) => old,
             Err(e) => {
                 signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
                 signal::sigaction(signal::Signal::SIGTERM, &sigterm_old).unwrap();
-                return Err(close_pipe(e));
+                return Err(close_pipe(e.into()));
+            }
+        };
+        if is_conflict(policy, sighup_old.handler()) {
+            signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
+            signal::sigaction(signal::Signal::SIGTERM, &sigterm_old).unwrap();
+            signal::sigaction(signal::Signal::SIGHUP, &sighup_old).unwrap();
+            return Err(close_pipe(CtrlcError::MultipleHandlers(Signal::SIGHUP)));
+        }
+        let sigquit_old = match signal::sigaction(signal::Signal::SIGQUIT, &new_action) {
+            Ok(old) => old,
+            Err(e) => {
+                signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
+                signal::sigaction(signal::Signal::SIGTERM, &sigterm_old).unwrap();
+                signal::sigaction(signal::Signal::SIGHUP, &sighup_old).unwrap();
+                return Err(close_pipe(e.into()));
             }
         };
-        if !overwrite && sighup_old.handler() != signal::SigHandler::SigDfl {
+        if is_conflict(policy, sigquit_old.handler()) {
             signal::sigaction(signal::Signal::SIGINT, &sigint_old).unwrap();
             signal::sigaction(signal::Signal::SIGTERM, &sigterm_old).unwrap();
             signal::sigaction(signal::Signal::SIGHUP, &sighup_old).unwrap();
-            return Err(close_pipe(nix::Error::EEXIST));
+            signal::sigaction(signal::Signal::SIGQUIT, &sigquit_old).unwrap();
+            return Err(close_pipe(CtrlcError::MultipleHandlers(Signal::SIGQUIT)));
         }
     }
 
     Ok(())
 }
 
+/// Prepares the thread calling [`block_ctrl_c()`](fn.block_ctrl_c.html).
+///
+/// Unblocks the handled signals in the calling thread, which inherits the signal mask of the
+/// thread that set the handler. If they were blocked there and in every other thread, no thread
+/// would run the OS handler and the signals would stay pending forever.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+pub fn init_handler_thread() -> Result<(), Error> {
+    use nix::sys::signal::{pthread_sigmask, SigSet, SigmaskHow};
+
+    let mut mask = SigSet::empty();
+    mask.add(Signal::SIGINT);
+    #[cfg(feature = "termination")]
+    {
+        mask.add(Signal::SIGTERM);
+        mask.add(Signal::SIGHUP);
+        mask.add(Signal::SIGQUIT);
+    }
+
+    pthread_sigmask(SigmaskHow::SIG_UNBLOCK, Some(&mask), None)
+}
+
 /// Blocks until a Ctrl-C signal is received.
 ///
 /// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
@@ -170,22 +460,86 @@ pub unsafe fn init_os_handler(overwrite: bool) -> Result<(), Error> {
 /// # Errors
 /// Will return an error if a system error occurred.
 ///
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
 #[inline]
-pub unsafe fn block_ctrl_c() -> Result<(), CtrlcError> {
+pub unsafe fn block_ctrl_c() -> Result<SignalInfo, CtrlcError> {
     use std::io;
-    let mut buf = [0u8];
+    let mut buf = [0u8; std::mem::size_of::<Record>()];
 
     // TODO: Can we safely convert the pipe fd into a std::io::Read
     // with std::os::unix::io::FromRawFd, this would handle EINTR
     // and everything for us.
     loop {
         match unistd::read(PIPE.0, &mut buf[..]) {
-            Ok(1) => break,
+            Ok(n) if n == buf.len() => break,
             Ok(_) => return Err(CtrlcError::System(io::ErrorKind::UnexpectedEof.into())),
             Err(nix::errno::Errno::EINTR) => {}
             Err(e) => return Err(e.into()),
         }
     }
 
+    let record = std::ptr::read_unaligned(buf.as_ptr() as *const Record);
+    Ok(signal_info(
+        record.signal,
+        record.pid as u32,
+        record.uid as u32,
+    ))
+}
+
+/// Wakes up [`block_ctrl_c()`](fn.block_ctrl_c.html) with `signal` without going through the OS
+/// signal machinery.
+///
+/// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[cfg(feature = "test-utils")]
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+#[inline]
+pub unsafe fn inject(signal: Signal) -> Result<(), Error> {
+    Record::new(signal as libc::c_int).write()?;
+    Ok(())
+}
+
+/// Wakes up [`block_ctrl_c()`](fn.block_ctrl_c.html) running in `thread` so that it can exit.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+#[inline]
+pub unsafe fn wake_ctrl_c(_thread: &std::thread::JoinHandle<()>) -> Result<(), Error> {
+    match Record::new(0).write() {
+        // The pipe is full, so the thread is going to wake up anyway.
+        Ok(_) | Err(nix::errno::Errno::EAGAIN) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Unregister os signal handler.
+///
+/// Restores the default disposition of the handled signals and closes the pipe. Must only be
+/// called once the thread calling [`block_ctrl_c()`](fn.block_ctrl_c.html) has stopped doing so.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+#[inline]
+pub unsafe fn deinit_os_handler() -> Result<(), Error> {
+    revert_sighandler_to_default(Signal::SIGINT)?;
+    #[cfg(feature = "termination")]
+    {
+        revert_sighandler_to_default(Signal::SIGTERM)?;
+        revert_sighandler_to_default(Signal::SIGHUP)?;
+        revert_sighandler_to_default(Signal::SIGQUIT)?;
+    }
+
+    // close() should not fail, but if it does, there isn't much we can do
+    let _ = unistd::close(PIPE.1);
+    let _ = unistd::close(PIPE.0);
+    PIPE = (-1, -1);
+
     Ok(())
 }
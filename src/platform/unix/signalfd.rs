@@ -0,0 +1,218 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! `signalfd(2)` based backend, enabled with the `signalfd` feature on Linux and Android.
+//!
+//! Instead of installing a signal handler, the handled signals are blocked and read from a
+//! signalfd stored in the read end slot of `PIPE`.
+
+use super::{Error, PIPE};
+use crate::error::Error as CtrlcError;
+use crate::signal::SignalInfo;
+use crate::ConflictPolicy;
+use nix::errno::Errno;
+use nix::libc;
+use nix::sys::signal::{self, SigSet, SigmaskHow, Signal};
+use nix::unistd;
+use std::mem::{self, MaybeUninit};
+use std::os::unix::io::RawFd;
+use std::ptr;
+
+// The thread that registered the handler and the signals it did not block before, unblocked again
+// by deinit_os_handler() if called from the same thread. Only accessed under INIT_LOCK, or in
+// the child after fork(2).
+static mut UNBLOCK: Option<(libc::pthread_t, SigSet)> = None;
+
+fn handled_signals() -> SigSet {
+    let mut mask = SigSet::empty();
+    mask.add(Signal::SIGINT);
+    #[cfg(feature = "termination")]
+    {
+        mask.add(Signal::SIGTERM);
+        mask.add(Signal::SIGHUP);
+        mask.add(Signal::SIGQUIT);
+    }
+    mask
+}
+
+/// Register os signal handler.
+///
+/// Blocks the handled signals in the calling thread and creates a signalfd for them. Threads
+/// spawned afterwards inherit the signal mask, threads that already exist do not. Must be
+/// called before calling [`block_ctrl_c()`](fn.block_ctrl_c.html) and should only be called
+/// once, until [`deinit_os_handler()`](fn.deinit_os_handler.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[inline]
+pub unsafe fn init_os_handler(policy: ConflictPolicy) -> Result<(), CtrlcError> {
+    let mask = handled_signals();
+
+    super::register_atfork()?;
+
+    if policy != ConflictPolicy::Overwrite {
+        for signal in mask.iter() {
+            let mut old = MaybeUninit::<libc::sigaction>::uninit();
+            Errno::result(libc::sigaction(
+                signal as libc::c_int,
+                ptr::null(),
+                old.as_mut_ptr(),
+            ))?;
+            let old = old.assume_init().sa_sigaction;
+            let ignored = policy == ConflictPolicy::ErrorOnCustomOnly && old == libc::SIG_IGN;
+            if old != libc::SIG_DFL && !ignored {
+                return Err(CtrlcError::MultipleHandlers(signal));
+            }
+        }
+    }
+
+    let mut old_mask = SigSet::empty();
+    signal::pthread_sigmask(SigmaskHow::SIG_BLOCK, Some(&mask), Some(&mut old_mask))?;
+
+    match Errno::result(libc::signalfd(-1, mask.as_ref(), libc::SFD_CLOEXEC)) {
+        Ok(fd) => PIPE.0 = fd,
+        Err(e) => {
+            let _ = signal::pthread_sigmask(SigmaskHow::SIG_SETMASK, Some(&old_mask), None);
+            return Err(e.into());
+        }
+    }
+
+    let mut unblock = SigSet::empty();
+    for signal in mask.iter().filter(|signal| !old_mask.contains(*signal)) {
+        unblock.add(signal);
+    }
+    UNBLOCK = Some((libc::pthread_self(), unblock));
+
+    Ok(())
+}
+
+/// Whether `handler` is the OS handler installed by [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// Always `false`, no handler is installed.
+pub fn is_os_handler(_handler: libc::sighandler_t) -> bool {
+    false
+}
+
+/// Prepares the thread calling [`block_ctrl_c()`](fn.block_ctrl_c.html).
+///
+/// Nothing to do, the handled signals must stay blocked to be read from the signalfd.
+///
+/// # Errors
+/// Never returns an error.
+///
+pub fn init_handler_thread() -> Result<(), Error> {
+    Ok(())
+}
+
+/// Blocks until a Ctrl-C signal is received.
+///
+/// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[inline]
+pub unsafe fn block_ctrl_c() -> Result<SignalInfo, CtrlcError> {
+    use std::io;
+    let mut buf = [0u8; mem::size_of::<libc::signalfd_siginfo>()];
+
+    loop {
+        match unistd::read(PIPE.0, &mut buf[..]) {
+            Ok(n) if n == buf.len() => break,
+            Ok(_) => return Err(CtrlcError::System(io::ErrorKind::UnexpectedEof.into())),
+            Err(nix::errno::Errno::EINTR) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let info = ptr::read_unaligned(buf.as_ptr() as *const libc::signalfd_siginfo);
+    Ok(super::signal_info(
+        info.ssi_signo as libc::c_int,
+        info.ssi_pid,
+        info.ssi_uid,
+    ))
+}
+
+/// Wakes up [`block_ctrl_c()`](fn.block_ctrl_c.html) by sending `signal` to the process.
+///
+/// A signalfd can not be written to, so the signal still goes through the OS. It is blocked
+/// though, so the only observer is the signalfd. Like any standard signal, injections that
+/// happen while a previous one is still pending are merged into one.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[cfg(feature = "test-utils")]
+#[inline]
+pub unsafe fn inject(signal: Signal) -> Result<(), Error> {
+    signal::kill(unistd::Pid::this(), signal)
+}
+
+/// Wakes up [`block_ctrl_c()`](fn.block_ctrl_c.html) running in `thread` so that it can exit.
+///
+/// Sends `SIGINT` to the thread, which only it can read from the signalfd.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[inline]
+pub unsafe fn wake_ctrl_c(thread: &std::thread::JoinHandle<()>) -> Result<(), Error> {
+    use std::os::unix::thread::JoinHandleExt;
+
+    Errno::result(libc::pthread_kill(thread.as_pthread_t(), libc::SIGINT)).map(drop)
+}
+
+/// Unregister os signal handler.
+///
+/// Discards the signals that were not read yet and closes the signalfd. If called from the
+/// thread that called [`init_os_handler()`](fn.init_os_handler.html), the handled signals are
+/// unblocked again in it, unless they were blocked before. Otherwise they stay blocked in that
+/// thread, as the signal mask of another thread can't be changed. Threads spawned while the
+/// handler was registered keep them blocked too.
+///
+/// Only calls async-signal-safe functions, as it is also called in the child after `fork(2)`.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[inline]
+pub unsafe fn deinit_os_handler() -> Result<(), Error> {
+    // Like signals left in the pipe of the default backend, signals received while the handler
+    // was registered must neither terminate the process once unblocked nor reach the next
+    // handler.
+    let mut pollfd = libc::pollfd {
+        fd: PIPE.0,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let mut buf = [0u8; mem::size_of::<libc::signalfd_siginfo>()];
+    while libc::poll(&mut pollfd, 1, 0) == 1
+        && libc::read(PIPE.0, buf.as_mut_ptr().cast(), buf.len()) > 0
+    {}
+
+    // close() should not fail, but if it does, there isn't much we can do
+    let _ = unistd::close(PIPE.0);
+    PIPE.0 = -1;
+
+    if let Some((thread, unblock)) = UNBLOCK {
+        UNBLOCK = None;
+        if libc::pthread_equal(thread, libc::pthread_self()) != 0 {
+            signal::pthread_sigmask(SigmaskHow::SIG_UNBLOCK, Some(&unblock), None)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The signalfd the signal handling thread reads from, or `-1` if no handler is registered.
+#[inline]
+pub fn signal_fd() -> RawFd {
+    unsafe { PIPE.0 }
+}
@@ -7,10 +7,17 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use crate::signal::{SignalInfo, SignalType};
 use std::io;
 use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU32, Ordering};
 use windows_sys::Win32::Foundation::{CloseHandle, BOOL, HANDLE, WAIT_FAILED, WAIT_OBJECT_0};
-use windows_sys::Win32::System::Console::SetConsoleCtrlHandler;
+#[cfg(feature = "test-utils")]
+use windows_sys::Win32::System::Console::GenerateConsoleCtrlEvent;
+use windows_sys::Win32::System::Console::{
+    SetConsoleCtrlHandler, CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT, CTRL_LOGOFF_EVENT,
+    CTRL_SHUTDOWN_EVENT,
+};
 use windows_sys::Win32::System::Threading::{
     CreateSemaphoreA, ReleaseSemaphore, WaitForSingleObject, INFINITE,
 };
@@ -21,17 +28,131 @@ pub type Error = io::Error;
 /// Platform specific signal type
 pub type Signal = u32;
 
+/// Minimum stack size of the signal handling thread.
+pub const MIN_STACK_SIZE: usize = 0;
+
 const MAX_SEM_COUNT: i32 = 255;
-static mut SEMAPHORE: HANDLE = 0 as HANDLE;
+// Read by os_handler() on a thread created by the system, so it can't be a `static mut`. Only
+// written under INIT_LOCK, and os_handler() is unregistered before the handle is closed.
+static SEMAPHORE: AtomicPtr<std::ffi::c_void> = AtomicPtr::new(ptr::null_mut());
+// The event received last, the semaphore only counts them.
+static LAST_EVENT: AtomicU32 = AtomicU32::new(CTRL_C_EVENT);
+// The number of events received by os_handler(), raise_ctrl_c() waits for it to change.
+#[cfg(feature = "test-utils")]
+static RAISED_COUNT: AtomicU32 = AtomicU32::new(0);
+// The status handle of the service control handler registered last, if the process runs as a
+// service.
+#[cfg(feature = "windows-service")]
+static STATUS_HANDLE: std::sync::Mutex<
+    Option<windows_service::service_control_handler::ServiceStatusHandle>,
+> = std::sync::Mutex::new(None);
 const TRUE: BOOL = 1;
 const FALSE: BOOL = 0;
 
-unsafe extern "system" fn os_handler(_: u32) -> BOOL {
+/// Signal written for `SignalType::Ctrlc`.
+pub const CTRL_C_SIGNAL: Signal = CTRL_C_EVENT;
+
+/// Signal written for `SignalType::Termination`.
+pub const TERMINATION_SIGNAL: Signal = CTRL_CLOSE_EVENT;
+
+const SIGNAL_NAMES: [(Signal, &str); 5] = [
+    (CTRL_C_EVENT, "CTRL_C_EVENT"),
+    (CTRL_BREAK_EVENT, "CTRL_BREAK_EVENT"),
+    (CTRL_CLOSE_EVENT, "CTRL_CLOSE_EVENT"),
+    (CTRL_LOGOFF_EVENT, "CTRL_LOGOFF_EVENT"),
+    (CTRL_SHUTDOWN_EVENT, "CTRL_SHUTDOWN_EVENT"),
+];
+
+/// Get the name of a console control event.
+pub fn signal_name(signal: Signal) -> Option<&'static str> {
+    SIGNAL_NAMES
+        .iter()
+        .find(|(s, _)| *s == signal)
+        .map(|(_, name)| *name)
+}
+
+/// Get a console control event from its name, matched case-insensitively.
+pub fn signal_from_name(name: &str) -> Option<Signal> {
+    SIGNAL_NAMES
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|(signal, _)| *signal)
+}
+
+/// Iterate over all console control events.
+pub fn signal_iterator() -> impl Iterator<Item = Signal> {
+    SIGNAL_NAMES.iter().map(|(signal, _)| *signal)
+}
+
+/// Get a console control event from its number.
+pub fn signal_from_raw(num: i32) -> Option<Signal> {
+    SIGNAL_NAMES
+        .iter()
+        .find(|(s, _)| *s as i32 == num)
+        .map(|(signal, _)| *signal)
+}
+
+/// Whether `signal` is delivered to every process attached to the console.
+pub fn is_terminal_signal(signal: Signal) -> bool {
+    matches!(signal, CTRL_C_EVENT | CTRL_BREAK_EVENT)
+}
+
+fn semaphore() -> HANDLE {
+    SEMAPHORE.load(Ordering::Acquire)
+}
+
+// Every console control event is routed to the handler, not only CTRL_C_EVENT.
+unsafe extern "system" fn os_handler(event: u32) -> BOOL {
+    LAST_EVENT.store(event, Ordering::Relaxed);
+    #[cfg(feature = "test-utils")]
+    RAISED_COUNT.fetch_add(1, Ordering::Release);
     // Assuming this always succeeds. Can't really handle errors in any meaningful way.
-    ReleaseSemaphore(SEMAPHORE, 1, ptr::null_mut());
+    ReleaseSemaphore(semaphore(), 1, ptr::null_mut());
     TRUE
 }
 
+// Delivers the stop requests of the service control manager like console control events.
+#[cfg(feature = "windows-service")]
+fn service_handler(
+    control: windows_service::service::ServiceControl,
+) -> windows_service::service_control_handler::ServiceControlHandlerResult {
+    use windows_service::service::ServiceControl;
+    use windows_service::service_control_handler::ServiceControlHandlerResult;
+
+    match control {
+        ServiceControl::Stop | ServiceControl::Shutdown | ServiceControl::Preshutdown => {
+            // Can't really handle errors in any meaningful way.
+            let _ = unsafe { inject(CTRL_C_SIGNAL) };
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    }
+}
+
+/// Register a service control handler for `service_name` that wakes up
+/// [`block_ctrl_c()`](fn.block_ctrl_c.html) on stop requests.
+///
+/// # Errors
+/// Will return an error if the process doesn't run as a service or a system error occurred.
+///
+#[cfg(feature = "windows-service")]
+pub fn register_service_handler(
+    service_name: impl AsRef<std::ffi::OsStr>,
+) -> windows_service::Result<windows_service::service_control_handler::ServiceStatusHandle> {
+    let status_handle =
+        windows_service::service_control_handler::register(service_name, service_handler)?;
+    *STATUS_HANDLE.lock().unwrap_or_else(|e| e.into_inner()) = Some(status_handle);
+    Ok(status_handle)
+}
+
+/// Get the status handle of the service control handler registered last.
+#[cfg(feature = "windows-service")]
+pub fn service_status_handle(
+) -> Option<windows_service::service_control_handler::ServiceStatusHandle> {
+    *STATUS_HANDLE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 /// Register os signal handler.
 ///
 /// Must be called before calling [`block_ctrl_c()`](fn.block_ctrl_c.html)
@@ -41,19 +162,36 @@ unsafe extern "system" fn os_handler(_: u32) -> BOOL {
 /// Will return an error if a system error occurred.
 ///
 #[inline]
-pub unsafe fn init_os_handler(_overwrite: bool) -> Result<(), Error> {
-    SEMAPHORE = CreateSemaphoreA(ptr::null_mut(), 0, MAX_SEM_COUNT, ptr::null());
-    if SEMAPHORE.is_null() {
+pub unsafe fn init_os_handler(_policy: crate::ConflictPolicy) -> Result<(), Error> {
+    let handle = CreateSemaphoreA(ptr::null_mut(), 0, MAX_SEM_COUNT, ptr::null());
+    if handle.is_null() {
         return Err(io::Error::last_os_error());
     }
+    SEMAPHORE.store(handle, Ordering::Release);
 
     if SetConsoleCtrlHandler(Some(os_handler), TRUE) == FALSE {
         let e = io::Error::last_os_error();
-        CloseHandle(SEMAPHORE);
-        SEMAPHORE = 0 as HANDLE;
+        SEMAPHORE.store(ptr::null_mut(), Ordering::Release);
+        CloseHandle(handle);
         return Err(e);
     }
 
+    // Registering only succeeds from within a running service, otherwise just console control
+    // events are handled. The name is ignored for services running in their own process.
+    #[cfg(feature = "windows-service")]
+    let _ = register_service_handler("");
+
+    Ok(())
+}
+
+/// Prepares the thread calling [`block_ctrl_c()`](fn.block_ctrl_c.html).
+///
+/// Nothing to do, handler routines run on a thread created by the system.
+///
+/// # Errors
+/// Never returns an error.
+///
+pub fn init_handler_thread() -> Result<(), Error> {
     Ok(())
 }
 
@@ -65,9 +203,19 @@ pub unsafe fn init_os_handler(_overwrite: bool) -> Result<(), Error> {
 /// Will return an error if a system error occurred.
 ///
 #[inline]
-pub unsafe fn block_ctrl_c() -> Result<(), Error> {
-    match WaitForSingleObject(SEMAPHORE, INFINITE) {
-        WAIT_OBJECT_0 => Ok(()),
+pub unsafe fn block_ctrl_c() -> Result<SignalInfo, Error> {
+    match WaitForSingleObject(semaphore(), INFINITE) {
+        WAIT_OBJECT_0 => {
+            let signal = match LAST_EVENT.load(Ordering::Relaxed) {
+                CTRL_C_EVENT | CTRL_BREAK_EVENT => SignalType::Ctrlc,
+                event => SignalType::from_platform_signal(event),
+            };
+            Ok(SignalInfo {
+                signal,
+                sender_pid: None,
+                sender_uid: None,
+            })
+        }
         WAIT_FAILED => Err(io::Error::last_os_error()),
         ret => Err(io::Error::new(
             io::ErrorKind::Other,
@@ -78,3 +226,116 @@ pub unsafe fn block_ctrl_c() -> Result<(), Error> {
         )),
     }
 }
+
+/// Wakes up [`block_ctrl_c()`](fn.block_ctrl_c.html) with the console control event `signal`
+/// without going through the OS console control machinery.
+///
+/// Must be called after calling [`init_os_handler()`](fn.init_os_handler.html).
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[cfg(any(feature = "test-utils", feature = "windows-service"))]
+#[inline]
+pub unsafe fn inject(signal: Signal) -> Result<(), Error> {
+    LAST_EVENT.store(signal, Ordering::Relaxed);
+    if ReleaseSemaphore(semaphore(), 1, ptr::null_mut()) == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Wakes up [`block_ctrl_c()`](fn.block_ctrl_c.html) running in `thread` so that it can exit.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[inline]
+pub unsafe fn wake_ctrl_c(_thread: &std::thread::JoinHandle<()>) -> Result<(), Error> {
+    if ReleaseSemaphore(semaphore(), 1, ptr::null_mut()) == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Unregister os signal handler.
+///
+/// Must only be called once the thread calling [`block_ctrl_c()`](fn.block_ctrl_c.html) has
+/// stopped doing so.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+///
+#[inline]
+pub unsafe fn deinit_os_handler() -> Result<(), Error> {
+    if SetConsoleCtrlHandler(Some(os_handler), FALSE) == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+
+    // A service control handler can't be unregistered, service_handler() just fails to wake
+    // anything up from now on.
+    CloseHandle(SEMAPHORE.swap(ptr::null_mut(), Ordering::AcqRel));
+
+    Ok(())
+}
+
+/// Generates `CTRL_C_EVENT` for the process only.
+///
+/// The event is delivered to every process attached to a console, so the process moves to a new
+/// hidden console for the duration of the call and then attaches to the console of its parent
+/// again, if it had a console before. The standard handles are restored afterwards.
+///
+/// # Errors
+/// Will return an error if the event wasn't received within a second or a system error occurred.
+///
+#[cfg(feature = "test-utils")]
+pub unsafe fn raise_ctrl_c() -> Result<(), Error> {
+    use std::time::{Duration, Instant};
+    use windows_sys::Win32::System::Console::{
+        AllocConsole, AttachConsole, FreeConsole, GetConsoleWindow, GetStdHandle, SetStdHandle,
+        ATTACH_PARENT_PROCESS, STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_HIDE};
+
+    let std_handles = [STD_INPUT_HANDLE, STD_OUTPUT_HANDLE, STD_ERROR_HANDLE]
+        .map(|std_handle| (std_handle, GetStdHandle(std_handle)));
+    let had_console = !GetConsoleWindow().is_null();
+
+    // FreeConsole() fails if there is no console, which is fine.
+    FreeConsole();
+    if AllocConsole() == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+    // The return value is the previous visibility of the window, not an error.
+    ShowWindow(GetConsoleWindow(), SW_HIDE);
+
+    let count = RAISED_COUNT.load(Ordering::Acquire);
+    let mut ret = if GenerateConsoleCtrlEvent(CTRL_C_EVENT, 0) == FALSE {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    };
+
+    // The handler routine runs asynchronously, leaving the console before it ran would lose the
+    // event.
+    let start = Instant::now();
+    while ret.is_ok() && RAISED_COUNT.load(Ordering::Acquire) == count {
+        if start.elapsed() > Duration::from_secs(1) {
+            ret = Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "CTRL_C_EVENT was not received",
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    FreeConsole();
+    if had_console && AttachConsole(ATTACH_PARENT_PROCESS) == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+    for (std_handle, handle) in std_handles {
+        SetStdHandle(std_handle, handle);
+    }
+
+    ret
+}
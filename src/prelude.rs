@@ -0,0 +1,22 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! The most commonly used items, for glob importing.
+//!
+//! # Example
+//! ```no_run
+//! use ctrlc::prelude::*;
+//!
+//! set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+//! ```
+
+pub use crate::{
+    set_handler, set_scoped_handler, try_set_handler, try_set_scoped_handler, Error, Signal,
+    SignalType,
+};
@@ -8,16 +8,249 @@
 // according to those terms.
 
 use crate::platform;
+use std::fmt;
+use std::str::FromStr;
 
 /// A cross-platform way to represent Ctrl-C or program termination signal. Other
 /// signals/events are supported via `Other`-variant.
-#[derive(Debug)]
+///
+/// `SignalType` converts to and from the platform name of the signal, e.g. `"SIGINT"` or
+/// `"CTRL_C_EVENT"`. `Ctrlc` and `Termination` are written as `SIGINT` and `SIGTERM` on Unix and
+/// as `CTRL_C_EVENT` and `CTRL_CLOSE_EVENT` on Windows, and those names are parsed back to them
+/// rather than to `Other`.
+///
+/// # Example
+/// ```
+/// use ctrlc::SignalType;
+///
+/// let signal: SignalType = "sigint".parse().unwrap();
+/// # #[cfg(unix)]
+/// assert_eq!(signal.to_string(), "SIGINT");
+/// assert_eq!(signal, SignalType::Ctrlc);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SignalType {
     /// Ctrl-C
     Ctrlc,
     /// Program termination
-    /// Maps to `SIGTERM` and `SIGHUP` on *nix, `CTRL_CLOSE_EVENT` on Windows.
+    /// Maps to `SIGTERM` on *nix, `CTRL_CLOSE_EVENT` on Windows.
     Termination,
+    /// Hangup of the controlling terminal, commonly used to reload configuration
+    /// Maps to `SIGHUP`, only available on *nix. Handled with the `termination` feature.
+    #[cfg(unix)]
+    Hangup,
+    /// Quit from the keyboard, Ctrl-\\
+    /// Maps to `SIGQUIT`, only available on *nix. Handled with the `termination` feature.
+    #[cfg(unix)]
+    Quit,
+    /// Terminal window size change
+    /// Maps to `SIGWINCH`, only available on *nix.
+    #[cfg(unix)]
+    WindowChange,
+    /// User-defined signal 1
+    /// Maps to `SIGUSR1`, only available on *nix.
+    #[cfg(unix)]
+    UserDefined1,
+    /// User-defined signal 2
+    /// Maps to `SIGUSR2`, only available on *nix.
+    #[cfg(unix)]
+    UserDefined2,
     /// Other signal/event using platform-specific data
     Other(platform::Signal),
 }
+
+/// Alias of [`SignalType::Ctrlc`].
+pub const CTRL_C_SIGNAL: SignalType = SignalType::Ctrlc;
+
+impl SignalType {
+    /// Alias of `Ctrlc`, after the Windows `CTRL_C_EVENT`.
+    pub const CTRL_C: SignalType = SignalType::Ctrlc;
+    /// Alias of `Ctrlc`, after the signal it maps to on Unix.
+    #[cfg(unix)]
+    pub const SIGINT: SignalType = SignalType::Ctrlc;
+    /// Alias of `Termination`, after the signal it is written as on Unix.
+    #[cfg(unix)]
+    pub const SIGTERM: SignalType = SignalType::Termination;
+
+    /// Whether the handler registered with `set_handler()` is executed for this signal.
+    pub(crate) fn is_handled(&self) -> bool {
+        match *self {
+            SignalType::Ctrlc => true,
+            // The Windows handler routine receives every console control event.
+            SignalType::Termination => cfg!(any(windows, feature = "termination")),
+            #[cfg(unix)]
+            SignalType::Hangup | SignalType::Quit => cfg!(feature = "termination"),
+            #[cfg(unix)]
+            SignalType::WindowChange | SignalType::UserDefined1 | SignalType::UserDefined2 => false,
+            SignalType::Other(_) => false,
+        }
+    }
+
+    /// The platform signal this signal type is written as.
+    pub(crate) fn platform_signal(&self) -> platform::Signal {
+        match *self {
+            SignalType::Ctrlc => platform::CTRL_C_SIGNAL,
+            SignalType::Termination => platform::TERMINATION_SIGNAL,
+            #[cfg(unix)]
+            SignalType::Hangup => platform::Signal::SIGHUP,
+            #[cfg(unix)]
+            SignalType::Quit => platform::Signal::SIGQUIT,
+            #[cfg(unix)]
+            SignalType::WindowChange => platform::Signal::SIGWINCH,
+            #[cfg(unix)]
+            SignalType::UserDefined1 => platform::Signal::SIGUSR1,
+            #[cfg(unix)]
+            SignalType::UserDefined2 => platform::Signal::SIGUSR2,
+            SignalType::Other(signal) => signal,
+        }
+    }
+
+    /// The signal type written as `signal`.
+    pub(crate) fn from_platform_signal(signal: platform::Signal) -> SignalType {
+        match signal {
+            platform::CTRL_C_SIGNAL => SignalType::Ctrlc,
+            platform::TERMINATION_SIGNAL => SignalType::Termination,
+            #[cfg(unix)]
+            platform::Signal::SIGHUP => SignalType::Hangup,
+            #[cfg(unix)]
+            platform::Signal::SIGQUIT => SignalType::Quit,
+            #[cfg(unix)]
+            platform::Signal::SIGWINCH => SignalType::WindowChange,
+            #[cfg(unix)]
+            platform::Signal::SIGUSR1 => SignalType::UserDefined1,
+            #[cfg(unix)]
+            platform::Signal::SIGUSR2 => SignalType::UserDefined2,
+            signal => SignalType::Other(signal),
+        }
+    }
+
+    /// Get the raw number of the signal this signal type is written as, e.g. `libc::SIGINT` for
+    /// `Ctrlc` on Unix and `CTRL_C_EVENT` on Windows.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(unix)]
+    /// assert_eq!(ctrlc::SignalType::Ctrlc.into_raw(), 2);
+    /// ```
+    #[inline]
+    #[must_use = "converting the signal type has no side effects"]
+    pub fn into_raw(self) -> i32 {
+        self.platform_signal() as i32
+    }
+
+    /// Get the signal type for a raw signal number, the inverse of
+    /// [`into_raw()`](#method.into_raw).
+    ///
+    /// Returns `None` if the number does not correspond to a signal on the current platform.
+    ///
+    /// # Example
+    /// ```
+    /// use ctrlc::SignalType;
+    ///
+    /// # #[cfg(unix)]
+    /// assert_eq!(SignalType::from_raw(2), Some(SignalType::Ctrlc));
+    /// assert_eq!(SignalType::from_raw(-1), None);
+    /// ```
+    #[inline]
+    pub fn from_raw(num: i32) -> Option<SignalType> {
+        platform::signal_from_raw(num).map(SignalType::from_platform_signal)
+    }
+
+    /// Whether the terminal delivers this signal to the whole foreground process group.
+    ///
+    /// Signals generated from the keyboard, such as Ctrl-C, are sent by the terminal to every
+    /// process in the foreground process group, which includes child processes that have not
+    /// been moved to a group of their own. This is why the handlers of child processes run too,
+    /// even though nothing sent them a signal explicitly. Signals sent with `kill(2)` to a
+    /// process ID, such as `SIGTERM` from a service manager, only reach that process.
+    ///
+    /// On Unix this is `true` for `SIGINT`, `SIGQUIT` and `SIGTSTP`. On Windows this is `true` for
+    /// `CTRL_C_EVENT` and `CTRL_BREAK_EVENT`, which are delivered to every process attached to
+    /// the console.
+    ///
+    /// # Example
+    /// ```
+    /// assert!(ctrlc::SignalType::Ctrlc.delivered_to_process_group_by_terminal());
+    /// assert!(!ctrlc::SignalType::Termination.delivered_to_process_group_by_terminal());
+    /// ```
+    #[must_use = "checking the signal origin has no side effects"]
+    pub fn delivered_to_process_group_by_terminal(&self) -> bool {
+        match *self {
+            SignalType::Ctrlc => true,
+            SignalType::Termination => false,
+            _ => platform::is_terminal_signal(self.platform_signal()),
+        }
+    }
+}
+
+impl fmt::Display for SignalType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let signal = self.platform_signal();
+        match platform::signal_name(signal) {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", signal),
+        }
+    }
+}
+
+impl FromStr for SignalType {
+    type Err = UnknownSignal;
+
+    fn from_str(s: &str) -> Result<SignalType, UnknownSignal> {
+        platform::signal_from_name(s)
+            .map(SignalType::from_platform_signal)
+            .ok_or_else(|| UnknownSignal(s.to_owned()))
+    }
+}
+
+/// Information about a received signal, passed to the handler set with
+/// [`set_handler_with_info()`](fn.set_handler_with_info.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalInfo {
+    /// The signal. On Windows, `CTRL_BREAK_EVENT` is reported as `Ctrlc`.
+    pub signal: SignalType,
+    /// Process ID of the sender, if the signal was sent by a process, e.g. with `kill(2)`. `None`
+    /// for signals generated by the kernel, such as Ctrl-C pressed in the terminal, and always
+    /// `None` on Windows.
+    pub sender_pid: Option<u32>,
+    /// Real user ID of the sender, available under the same conditions as `sender_pid`.
+    pub sender_uid: Option<u32>,
+}
+
+/// Converts to the signal `SignalType` is written as, e.g. for use with
+/// `nix::sys::signal::kill()`. `Termination` converts to `SIGTERM`.
+///
+/// # Example
+/// ```
+/// use ctrlc::{Signal, SignalType};
+///
+/// assert_eq!(Signal::from(SignalType::Ctrlc), Signal::SIGINT);
+/// ```
+#[cfg(unix)]
+impl From<SignalType> for platform::Signal {
+    fn from(signal: SignalType) -> platform::Signal {
+        signal.platform_signal()
+    }
+}
+
+/// Converts from a signal to the `SignalType` it is written as, e.g. `Hangup` for `SIGHUP` and
+/// `Other` for signals without a variant of their own.
+#[cfg(unix)]
+impl From<platform::Signal> for SignalType {
+    fn from(signal: platform::Signal) -> SignalType {
+        SignalType::from_platform_signal(signal)
+    }
+}
+
+/// Error returned when parsing a [`SignalType`] from a name that is not a signal on the current
+/// platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownSignal(pub String);
+
+impl fmt::Display for UnknownSignal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown signal: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownSignal {}
@@ -0,0 +1,47 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Direct access to the platform signals behind [`SignalType`](../enum.SignalType.html).
+//!
+//! [`Signal`] is `nix::sys::signal::Signal` on Unix and a console control event number on
+//! Windows, so code using this module has to be written separately for each platform. Prefer
+//! `SignalType` unless a signal without a portable equivalent is needed.
+//!
+//! # Example
+//! ```no_run
+//! # #[cfg(unix)]
+//! for signal in ctrlc::sys::signal_iterator().filter(|s| s.as_str().starts_with("SIGUSR")) {
+//!     ctrlc::sys::revert_sighandler_to_default(signal).unwrap();
+//! }
+//! assert!(ctrlc::sys::signal_iterator().any(|s| s == ctrlc::sys::CTRL_C_SIGNAL));
+//! ```
+
+#[cfg(unix)]
+use crate::error::Error;
+use crate::platform;
+
+pub use crate::platform::{Signal, CTRL_C_SIGNAL, TERMINATION_SIGNAL};
+
+/// Iterate over all signals of the current platform, console control events on Windows.
+pub fn signal_iterator() -> impl Iterator<Item = Signal> {
+    platform::signal_iterator()
+}
+
+/// Restore the default disposition of `signal`, removing any handler installed for it.
+///
+/// Reverting a signal handled by ctrlc stops its handler from being executed for that signal,
+/// but does not unregister it. Use [`remove_all_handlers()`](../fn.remove_all_handlers.html) for
+/// that.
+///
+/// # Errors
+/// Will return an error if a system error occurred, e.g. for `SIGKILL`.
+#[cfg(unix)]
+pub fn revert_sighandler_to_default(signal: Signal) -> Result<(), Error> {
+    unsafe { platform::revert_sighandler_to_default(signal) }.map_err(Error::from)
+}
@@ -0,0 +1,86 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Utilities for testing code that relies on a Ctrl-C handler.
+//!
+//! Only available with the `test-utils` feature. Not intended for production use.
+
+use crate::error::Error;
+use crate::platform;
+use crate::signal::SignalType;
+use std::io;
+use std::sync::atomic::Ordering;
+
+/// Deliver `signal` to the registered handler without raising an OS signal.
+///
+/// The signal is written directly to the channel the signal handling thread is waiting on, so
+/// the handler runs exactly as if the signal had been received from the system. This makes it
+/// possible to test handlers deterministically without process-wide signal delivery.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+/// ctrlc::testing::inject_signal(ctrlc::SignalType::Ctrlc).unwrap();
+/// ```
+///
+/// # Errors
+/// Will return [`Error::NoSuchSignal`] if the handler does not listen for `signal`, or
+/// [`Error::System`] if no handler is registered or a system error occurred.
+pub fn inject_signal(signal: SignalType) -> Result<(), Error> {
+    if !crate::INIT.load(Ordering::Acquire) {
+        return Err(Error::System(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no Ctrl-C handler registered",
+        )));
+    }
+
+    if !signal.is_handled() {
+        return Err(Error::NoSuchSignal(signal));
+    }
+
+    unsafe { platform::inject(signal.platform_signal())? };
+    Ok(())
+}
+
+/// Raise Ctrl-C through the OS, as if it had been pressed in the terminal.
+///
+/// Unlike [`inject_signal()`](fn.inject_signal.html), this goes through the same path as a real
+/// Ctrl-C, including the OS level handler.
+///
+/// On Unix, `SIGINT` is sent to the process with `kill(2)` rather than to the calling thread with
+/// `raise(3)`, so that it is also received with the `signalfd` feature. On Windows,
+/// `CTRL_C_EVENT` is delivered to every process attached to the console, so the process is moved
+/// to a new hidden console to generate it and attached to the console of its parent again
+/// afterwards. The standard handles are restored, but other console state is not, and other
+/// threads using the console in the meantime may fail.
+///
+/// The handler runs asynchronously on the signal handling thread, so it may not have run yet when
+/// this function returns.
+///
+/// # Example
+/// ```no_run
+/// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+/// ctrlc::testing::simulate_ctrl_c().unwrap();
+/// ```
+///
+/// # Errors
+/// Will return [`Error::System`] if no handler is registered, as the signal would then terminate
+/// the process, if a system error occurred, or on Windows if the event wasn't received within a
+/// second.
+pub fn simulate_ctrl_c() -> Result<(), Error> {
+    if !crate::INIT.load(Ordering::Acquire) {
+        return Err(Error::System(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no Ctrl-C handler registered",
+        )));
+    }
+
+    unsafe { platform::raise_ctrl_c()? };
+    Ok(())
+}
@@ -0,0 +1,588 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Unix specific functionality.
+
+use crate::error::Error;
+use crate::platform::{self, Signal};
+use crate::signal::SignalType;
+use nix::errno::Errno;
+use nix::libc;
+use nix::sys::signal::SigSet;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr;
+
+/// Get the canonical name of a signal number, e.g. `2 -> "SIGINT"`.
+///
+/// Returns `None` if the number does not correspond to a signal on the current platform.
+///
+/// # Example
+/// ```
+/// assert_eq!(ctrlc::unix::signal_number_to_name(2), Some("SIGINT"));
+/// ```
+#[must_use = "looking up the signal name has no side effects"]
+pub fn signal_number_to_name(num: i32) -> Option<&'static str> {
+    Signal::iterator()
+        .find(|signal| *signal as i32 == num)
+        .map(Signal::as_str)
+}
+
+/// Get the number of a signal from its name, e.g. `"SIGTERM" -> 15`. The name is matched
+/// case-insensitively.
+///
+/// Returns `None` if the name does not correspond to a signal on the current platform.
+///
+/// # Example
+/// ```
+/// assert_eq!(ctrlc::unix::signal_name_to_number("sigint"), Some(2));
+/// ```
+#[must_use = "looking up the signal number has no side effects"]
+pub fn signal_name_to_number(name: &str) -> Option<i32> {
+    platform::signal_from_name(name).map(|signal| signal as i32)
+}
+
+/// Check whether `signal` is pending for the calling thread, i.e. it has been delivered but is
+/// blocked by the signal mask. `SignalType::Termination` only checks `SIGTERM`, a pending
+/// `SIGHUP` is reported for `SignalType::Hangup`.
+///
+/// This is mostly useful for diagnosing why a handler is not running: a Ctrl-C that is pending
+/// has been suppressed by a signal mask.
+///
+/// # Errors
+/// Will return an error if `sigpending(2)` fails.
+pub fn is_signal_pending(signal: SignalType) -> Result<bool, Error> {
+    let pending = unsafe {
+        let mut set = MaybeUninit::<libc::sigset_t>::uninit();
+        Errno::result(libc::sigpending(set.as_mut_ptr()))?;
+        SigSet::from_sigset_t_unchecked(set.assume_init())
+    };
+
+    Ok(platform::platform_signals(&signal)
+        .into_iter()
+        .any(|signal| pending.contains(signal)))
+}
+
+/// The action installed for a signal, as returned by [`current_handler()`](fn.current_handler.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigactionInfo {
+    handler: libc::sighandler_t,
+}
+
+impl SigactionInfo {
+    /// Whether the signal has the default disposition (`SIG_DFL`).
+    #[must_use = "checking the disposition has no side effects"]
+    pub fn is_default(&self) -> bool {
+        self.handler == libc::SIG_DFL
+    }
+
+    /// Whether the signal is ignored (`SIG_IGN`).
+    #[must_use = "checking the disposition has no side effects"]
+    pub fn is_ignored(&self) -> bool {
+        self.handler == libc::SIG_IGN
+    }
+
+    /// Whether the handler is the one installed by ctrlc. Always `false` with the `signalfd`
+    /// feature on Linux and Android, which installs no handler.
+    #[must_use = "checking the disposition has no side effects"]
+    pub fn is_ctrlc(&self) -> bool {
+        platform::is_os_handler(self.handler)
+    }
+}
+
+/// Get the action currently installed for `signal` with `sigaction(2)`, e.g. to check whether
+/// another handler would be overwritten before registering one. For `SignalType::Termination`
+/// this is the action for `SIGTERM`.
+///
+/// # Example
+/// ```
+/// let action = ctrlc::unix::current_handler(ctrlc::SignalType::WindowChange).unwrap();
+/// assert!(action.is_default());
+/// ```
+///
+/// # Errors
+/// Will return an error if `sigaction(2)` fails.
+pub fn current_handler(signal: SignalType) -> Result<SigactionInfo, Error> {
+    let action = unsafe {
+        let mut old = MaybeUninit::<libc::sigaction>::uninit();
+        Errno::result(libc::sigaction(
+            Signal::from(signal) as libc::c_int,
+            ptr::null(),
+            old.as_mut_ptr(),
+        ))?;
+        old.assume_init()
+    };
+
+    Ok(SigactionInfo {
+        handler: action.sa_sigaction,
+    })
+}
+
+/// Priority class of a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalPriority {
+    /// A standard signal. Multiple instances of a pending standard signal are merged into one.
+    Standard,
+    /// A POSIX real-time signal, carrying its offset from `SIGRTMIN`. Real-time signals are
+    /// queued rather than merged and lower numbered ones are delivered first.
+    RealTime(u8),
+}
+
+/// Get the priority class of the signal number `signal`.
+///
+/// This takes a raw signal number because [`Signal`](../type.Signal.html) can only represent
+/// standard signals. Real-time signals only exist on Linux and Android, on other platforms every
+/// signal is standard.
+///
+/// # Example
+/// ```
+/// use ctrlc::unix::{signal_priority, SignalPriority};
+///
+/// assert_eq!(signal_priority(ctrlc::Signal::SIGINT as i32), SignalPriority::Standard);
+/// ```
+#[must_use = "looking up the signal priority has no side effects"]
+pub fn signal_priority(signal: i32) -> SignalPriority {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if (libc::SIGRTMIN()..=libc::SIGRTMAX()).contains(&signal) {
+        return SignalPriority::RealTime((signal - libc::SIGRTMIN()) as u8);
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let _ = signal;
+
+    SignalPriority::Standard
+}
+
+/// Forward Ctrl-C to the process group `pgid` and exit.
+///
+/// Registers a handler with [`set_handler()`](../fn.set_handler.html) that sends `SIGINT` to the
+/// process group with `killpg(3)` and then exits the current process with status 130, the
+/// status shells report for processes terminated by `SIGINT`. This is useful for shells and
+/// process managers that run children in their own process group, which therefore do not
+/// receive Ctrl-C from the terminal.
+///
+/// The processes in the group handle `SIGINT` concurrently with the exit of the current process,
+/// there is no waiting for them to terminate.
+///
+/// # Example
+/// ```no_run
+/// use std::os::unix::process::CommandExt;
+/// use std::process::Command;
+///
+/// let child = Command::new("sleep").arg("60").process_group(0).spawn().unwrap();
+/// ctrlc::unix::kill_process_group_on_ctrl_c(child.id() as i32)
+///     .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if a system error occurred while setting the handler.
+pub fn kill_process_group_on_ctrl_c(pgid: i32) -> Result<(), Error> {
+    crate::set_handler(move || {
+        // Can't really handle errors in any meaningful way.
+        let _ = nix::sys::signal::killpg(nix::unistd::Pid::from_raw(pgid), Signal::SIGINT);
+        std::process::exit(crate::SIGINT_EXIT_CODE);
+    })
+}
+
+/// Block the calling thread until it receives a signal handled by the registered handler, with
+/// its signal mask replaced by `sigset` while waiting.
+///
+/// The signal mask of the calling thread is replaced with `sigset` while waiting and restored
+/// before returning, so signals blocked by `sigset` can not interrupt the wait. The handler still
+/// runs on its own thread, this only lets the calling thread wait for it: the wait ends once the
+/// handler thread receives a signal, whichever thread it was delivered to. `SIGINT` is the only
+/// signal waited for unless the `termination` feature is enabled.
+///
+/// Not available with the `signalfd` feature on Linux and Android, where the handled signals are
+/// never delivered to a thread.
+///
+/// # Example
+/// ```no_run
+/// use nix::sys::signal::{SigSet, Signal};
+///
+/// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+/// // Wait for Ctrl-C, with everything but SIGINT blocked meanwhile.
+/// let mut sigset = SigSet::all();
+/// sigset.remove(Signal::SIGINT);
+/// unsafe { ctrlc::unix::block_ctrl_c_with_sigset(&sigset) }.unwrap();
+/// ```
+///
+/// # Errors
+/// Will return [`Error::System`] if no handler is registered, or is removed while waiting, as the
+/// signal would then terminate the process, or if a system error occurred.
+///
+/// # Safety
+/// Signals that `sigset` unblocks may run their handlers on the calling thread while it waits,
+/// including signals that the rest of the program expects to be blocked in it. The caller must
+/// make sure that this is fine for every signal handler installed in the process.
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+pub unsafe fn block_ctrl_c_with_sigset(sigset: &SigSet) -> Result<(), Error> {
+    use nix::sys::signal::{pthread_sigmask, SigmaskHow};
+    use std::sync::atomic::Ordering;
+
+    let mut received = crate::SIGNALS_RECEIVED.lock().unwrap();
+    if !crate::INIT.load(Ordering::Acquire) {
+        return Err(not_registered());
+    }
+
+    let mut old = SigSet::empty();
+    pthread_sigmask(SigmaskHow::SIG_SETMASK, Some(sigset), Some(&mut old))?;
+    let start = *received;
+    while *received == start && crate::INIT.load(Ordering::Acquire) {
+        received = crate::SIGNAL_RECEIVED.wait(received).unwrap();
+    }
+    pthread_sigmask(SigmaskHow::SIG_SETMASK, Some(&old), None)?;
+
+    if *received == start {
+        return Err(not_registered());
+    }
+    Ok(())
+}
+
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+fn not_registered() -> Error {
+    Error::System(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "no Ctrl-C handler registered",
+    ))
+}
+
+/// Number of signals that were received but never reached the handler.
+///
+/// Received signals are queued in a pipe for the signal handling thread. If the handler runs for
+/// long enough that the pipe fills up, around 5000 signals on Linux, further signals are dropped
+/// and counted here.
+///
+/// The count is process-global and is never reset: it accumulates across every handler
+/// registered during the lifetime of the process, so compare it against an earlier reading to
+/// tell whether signals were dropped while a specific handler was registered.
+///
+/// Not available with the `signalfd` feature, where repeated signals are merged by the kernel
+/// instead.
+#[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+pub fn dropped_signal_count() -> usize {
+    platform::DROPPED_SIGNALS.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Blocking and unblocking of the signals handled by ctrlc.
+#[derive(Debug)]
+pub struct SignalMask;
+
+impl SignalMask {
+    /// Block the signals handled by the registered handler in the calling thread, e.g. for a
+    /// critical section that must not be interrupted.
+    ///
+    /// Signals that arrive while blocked stay pending and are delivered once the returned guard
+    /// is dropped, which unblocks the signals that were not blocked before. If no handler is
+    /// registered, no signals are blocked.
+    ///
+    /// # Example
+    /// ```no_run
+    /// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+    /// {
+    ///     let _guard = ctrlc::unix::SignalMask::block_all().unwrap();
+    ///     // Ctrl-C is deferred until the end of this block
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Will return an error if `pthread_sigmask(3)` fails.
+    pub fn block_all() -> Result<SignalMaskGuard, Error> {
+        SignalMask::change(nix::sys::signal::SigmaskHow::SIG_BLOCK)
+    }
+
+    /// Unblock the signals handled by the registered handler in the calling thread, e.g. in a
+    /// thread that inherited a signal mask blocking them.
+    ///
+    /// The returned guard blocks the signals that were blocked before again when dropped. If no
+    /// handler is registered, no signals are unblocked.
+    ///
+    /// Not available with the `signalfd` feature, where the handled signals must stay blocked.
+    ///
+    /// # Example
+    /// ```no_run
+    /// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+    /// let _guard = ctrlc::unix::SignalMask::unblock_all().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    /// Will return an error if `pthread_sigmask(3)` fails.
+    #[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+    pub fn unblock_all() -> Result<SignalMaskGuard, Error> {
+        SignalMask::change(nix::sys::signal::SigmaskHow::SIG_UNBLOCK)
+    }
+
+    fn change(how: nix::sys::signal::SigmaskHow) -> Result<SignalMaskGuard, Error> {
+        use nix::sys::signal::{pthread_sigmask, SigmaskHow};
+        use std::sync::atomic::Ordering;
+
+        let mut mask = SigSet::empty();
+        if crate::INIT.load(Ordering::Acquire) {
+            for signal in [SignalType::Ctrlc, SignalType::Termination, SignalType::Quit] {
+                if signal.is_handled() {
+                    for signal in platform::platform_signals(&signal) {
+                        mask.add(signal);
+                    }
+                }
+            }
+        }
+
+        let mut old_mask = SigSet::empty();
+        pthread_sigmask(how, Some(&mask), Some(&mut old_mask))?;
+
+        // Only the signals whose state changed are restored.
+        let blocking = how == SigmaskHow::SIG_BLOCK;
+        let mut restore = SigSet::empty();
+        for signal in mask
+            .iter()
+            .filter(|signal| old_mask.contains(*signal) != blocking)
+        {
+            restore.add(signal);
+        }
+
+        Ok(SignalMaskGuard {
+            restore,
+            how: if blocking {
+                SigmaskHow::SIG_UNBLOCK
+            } else {
+                SigmaskHow::SIG_BLOCK
+            },
+            _not_send: PhantomData,
+        })
+    }
+}
+
+/// Guard returned by [`SignalMask::block_all()`](struct.SignalMask.html#method.block_all) and
+/// [`SignalMask::unblock_all()`](struct.SignalMask.html#method.unblock_all).
+///
+/// Restores the previous state of the signals on drop, which must happen on the thread that
+/// changed them.
+#[derive(Debug)]
+#[must_use = "the signals are restored when the guard is dropped"]
+pub struct SignalMaskGuard {
+    restore: SigSet,
+    how: nix::sys::signal::SigmaskHow,
+    // The signal mask is per-thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for SignalMaskGuard {
+    fn drop(&mut self) {
+        use nix::sys::signal::pthread_sigmask;
+
+        // Can't really handle errors in any meaningful way.
+        let _ = pthread_sigmask(self.how, Some(&self.restore), None);
+    }
+}
+
+/// Alternate signal stack installed by
+/// [`configure_sigaltstack()`](fn.configure_sigaltstack.html).
+///
+/// The previous alternate signal stack of the thread is restored and the memory of this one
+/// freed on drop. The guard must be dropped on the thread that created it.
+#[derive(Debug)]
+#[must_use = "the alternate signal stack is removed when the guard is dropped"]
+pub struct SigAltStackGuard {
+    old: libc::stack_t,
+    _stack: Box<[u8]>,
+    // Alternate signal stacks are per-thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for SigAltStackGuard {
+    fn drop(&mut self) {
+        // Can't really handle errors in any meaningful way.
+        unsafe {
+            libc::sigaltstack(&self.old, ptr::null_mut());
+        }
+    }
+}
+
+/// Install an alternate signal stack of `size` bytes for the calling thread.
+///
+/// The signal handlers registered by ctrlc use `SA_ONSTACK`, so whenever a signal handled by
+/// ctrlc is delivered to this thread the handler runs on the alternate stack instead of the
+/// thread's own stack. This keeps Ctrl-C working in threads that are close to exhausting their
+/// stack, and is required by programs that also handle `SIGSEGV` from stack overflows.
+///
+/// Only affects the calling thread. Use
+/// [`HandlerBuilder::sigaltstack()`](../struct.HandlerBuilder.html#method.sigaltstack) for the
+/// signal handling thread.
+///
+/// # Example
+/// ```no_run
+/// let _guard = ctrlc::unix::configure_sigaltstack(64 * 1024).unwrap();
+/// ctrlc::set_handler(|| println!("Hello world!")).expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if `sigaltstack(2)` fails, e.g. if `size` is less than `MINSIGSTKSZ` or
+/// the thread is currently executing on its alternate signal stack.
+pub fn configure_sigaltstack(size: usize) -> Result<SigAltStackGuard, Error> {
+    let mut stack = vec![0u8; size].into_boxed_slice();
+
+    let new = libc::stack_t {
+        ss_sp: stack.as_mut_ptr().cast(),
+        ss_flags: 0,
+        ss_size: size,
+    };
+
+    let old = unsafe {
+        let mut old = MaybeUninit::<libc::stack_t>::uninit();
+        Errno::result(libc::sigaltstack(&new, old.as_mut_ptr()))?;
+        old.assume_init()
+    };
+
+    Ok(SigAltStackGuard {
+        old,
+        _stack: stack,
+        _not_send: PhantomData,
+    })
+}
+
+/// Get the signalfd the signal handling thread reads signals from.
+///
+/// Only available with the `signalfd` feature on Linux and Android. In that mode the signals
+/// handled by ctrlc are blocked and received through a `signalfd(2)` instead of a signal handler.
+/// The file descriptor can be registered with `epoll` or an async runtime's reactor to be
+/// notified of signals. Reading from it consumes the signal, so the handler is not executed for
+/// signals read by the application.
+///
+/// Returns `-1` if no handler has been registered.
+///
+/// Signals handled through a signalfd must be blocked in every thread, so the handler should be
+/// registered before any other threads are spawned. Signals directed to a specific thread, such
+/// as those sent by `raise(3)`, are only visible when reading the signalfd from that thread.
+#[cfg(all(feature = "signalfd", any(target_os = "linux", target_os = "android")))]
+#[must_use = "the signalfd is only useful to poll or read from"]
+pub fn signal_fd() -> std::os::unix::io::RawFd {
+    platform::signal_fd()
+}
+
+/// A `signalfd(2)` for a set of signals, available on Linux and Android.
+///
+/// Creating a `SignalFd` blocks its signals in the calling thread, so they are no longer
+/// delivered to signal handlers in this thread and queue up in the file descriptor instead. The
+/// signals that were not blocked before are unblocked again on drop, which must happen on the
+/// thread that created the `SignalFd`.
+///
+/// Reading yields one `signalfd_siginfo` structure per signal as raw bytes, the buffer must be at
+/// least that large. [`read_siginfo()`](#method.read_siginfo) returns the parsed structure.
+///
+/// To receive signals sent to the whole process, they must be blocked in every thread. Signals
+/// sent to a specific thread are only visible when reading from that thread.
+///
+/// # Example
+/// ```no_run
+/// use ctrlc::SignalType;
+/// use ctrlc::Signal;
+///
+/// let mut fd = ctrlc::unix::SignalFd::new(&[SignalType::Other(Signal::SIGUSR1)]).unwrap();
+/// let info = fd.read_siginfo().unwrap();
+/// println!("Got signal {} from pid {}", info.ssi_signo, info.ssi_pid);
+/// ```
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+pub struct SignalFd {
+    fd: std::os::fd::OwnedFd,
+    unblock: SigSet,
+    // The signal mask is per-thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl SignalFd {
+    /// Block `signals` in the calling thread and create a signalfd for them.
+    ///
+    /// # Errors
+    /// Will return an error if a system error occurred.
+    pub fn new(signals: &[SignalType]) -> Result<SignalFd, Error> {
+        use nix::sys::signal::{pthread_sigmask, SigmaskHow};
+        use std::os::fd::{FromRawFd, OwnedFd};
+
+        let mut mask = SigSet::empty();
+        for signal in signals {
+            for signal in platform::platform_signals(signal) {
+                mask.add(signal);
+            }
+        }
+
+        let mut old_mask = SigSet::empty();
+        pthread_sigmask(SigmaskHow::SIG_BLOCK, Some(&mask), Some(&mut old_mask))?;
+
+        let mut unblock = SigSet::empty();
+        for signal in mask.iter().filter(|signal| !old_mask.contains(*signal)) {
+            unblock.add(signal);
+        }
+
+        match Errno::result(unsafe { libc::signalfd(-1, mask.as_ref(), libc::SFD_CLOEXEC) }) {
+            Ok(fd) => Ok(SignalFd {
+                fd: unsafe { OwnedFd::from_raw_fd(fd) },
+                unblock,
+                _not_send: PhantomData,
+            }),
+            Err(e) => {
+                let _ = pthread_sigmask(SigmaskHow::SIG_UNBLOCK, Some(&unblock), None);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Block until a signal is received and return its information.
+    ///
+    /// # Errors
+    /// Will return an error if a system error occurred.
+    pub fn read_siginfo(&mut self) -> Result<libc::signalfd_siginfo, Error> {
+        use std::io::Read;
+
+        let mut info = MaybeUninit::<libc::signalfd_siginfo>::zeroed();
+        // signalfd_siginfo is plain old data, any bytes are a valid value.
+        let buf = unsafe {
+            std::slice::from_raw_parts_mut(
+                info.as_mut_ptr().cast::<u8>(),
+                std::mem::size_of::<libc::signalfd_siginfo>(),
+            )
+        };
+        self.read_exact(buf).map_err(Error::System)?;
+        Ok(unsafe { info.assume_init() })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl std::io::Read for SignalFd {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        use std::os::fd::AsRawFd;
+
+        Ok(nix::unistd::read(self.fd.as_raw_fd(), buf)?)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl std::os::fd::AsFd for SignalFd {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl std::os::fd::AsRawFd for SignalFd {
+    fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Drop for SignalFd {
+    fn drop(&mut self) {
+        use nix::sys::signal::{pthread_sigmask, SigmaskHow};
+
+        // Can't really handle errors in any meaningful way.
+        let _ = pthread_sigmask(SigmaskHow::SIG_UNBLOCK, Some(&self.unblock), None);
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Helpers for common Ctrl-C handling patterns.
+
+use crate::error::Error;
+use std::cmp;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Shortest wait of [`exponential_backoff_on_signal()`].
+const MIN_BACKOFF: Duration = Duration::from_millis(1);
+
+/// Call `body` repeatedly until it returns `false` or Ctrl-C is received.
+///
+/// A scoped handler is registered for the duration of the call and removed before returning,
+/// see [`set_scoped_handler()`](../fn.set_scoped_handler.html). Ctrl-C is only checked between
+/// calls, so `body` should return regularly.
+///
+/// # Example
+/// ```no_run
+/// let mut n = 0u64;
+/// ctrlc::util::run_until_ctrl_c(|| {
+///     n += 1;
+///     true
+/// })
+/// .expect("Error setting Ctrl-C handler");
+/// println!("Counted to {} before Ctrl-C", n);
+/// ```
+///
+/// # Errors
+/// Will return an error if another handler exists or if a system error occurred while setting the
+/// handler.
+pub fn run_until_ctrl_c<F>(mut body: F) -> Result<(), Error>
+where
+    F: FnMut() -> bool,
+{
+    let running = Arc::new(AtomicBool::new(true));
+    let r = Arc::clone(&running);
+
+    let _guard = crate::set_scoped_handler(move || r.store(false, Ordering::SeqCst))?;
+
+    while running.load(Ordering::SeqCst) && body() {}
+
+    Ok(())
+}
+
+/// Call `f` until it returns `true`, waiting between attempts with exponential backoff, or until
+/// Ctrl-C is received.
+///
+/// `f` returns `true` once the operation succeeded, and `false` to be retried after the next
+/// wait. Without that result, only Ctrl-C could end the retries.
+///
+/// The wait starts at `initial` and doubles after every attempt, up to `max`. Intervals below one
+/// millisecond are raised to one millisecond, so a zero `initial` or `max` doesn't busy-loop.
+/// Ctrl-C ends the wait immediately rather than after the current interval. A scoped handler is
+/// registered for the duration of the call and removed before returning, see
+/// [`set_scoped_handler()`](../fn.set_scoped_handler.html). Panics in `f` are propagated.
+///
+/// # Example
+/// ```no_run
+/// use std::net::TcpStream;
+/// use std::time::Duration;
+///
+/// let mut stream = None;
+/// ctrlc::util::exponential_backoff_on_signal(
+///     Duration::from_millis(100),
+///     Duration::from_secs(10),
+///     || {
+///         stream = TcpStream::connect("127.0.0.1:8080").ok();
+///         stream.is_some()
+///     },
+/// )
+/// .expect("Error setting Ctrl-C handler");
+/// ```
+///
+/// # Errors
+/// Will return an error if another handler exists or if a system error occurred while setting the
+/// handler.
+pub fn exponential_backoff_on_signal<F>(
+    initial: Duration,
+    max: Duration,
+    mut f: F,
+) -> Result<(), Error>
+where
+    F: FnMut() -> bool,
+{
+    let (tx, rx) = mpsc::channel();
+    let _guard = crate::set_scoped_handler(move || {
+        let _ = tx.send(());
+    })?;
+
+    let max = cmp::max(max, MIN_BACKOFF);
+    let mut backoff = cmp::min(cmp::max(initial, MIN_BACKOFF), max);
+    while !f() {
+        match rx.recv_timeout(backoff) {
+            Err(RecvTimeoutError::Timeout) => {}
+            _ => break,
+        }
+        backoff = cmp::min(backoff.saturating_mul(2), max);
+    }
+
+    Ok(())
+}
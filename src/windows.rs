@@ -0,0 +1,124 @@
+// Copyright (c) 2017 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Windows specific functionality.
+
+use crate::error::Error;
+use std::io;
+use windows_sys::Win32::System::Console::{AllocConsole, FreeConsole, GetConsoleWindow};
+use windows_sys::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_HIDE};
+
+/// Detach from the console of the parent process and allocate a new one for this process.
+///
+/// Console control events are delivered to every process attached to a console. In particular
+/// [`CTRL_BREAK_EVENT`][GenerateConsoleCtrlEvent] sent to a console terminates all processes
+/// in it whose handlers do not handle the event, including child processes that never
+/// registered a handler. Moving the current process to its own console isolates it from events
+/// generated on the parent's console, so pressing Ctrl+Break in the parent no longer reaches
+/// this process or the children it spawns afterwards.
+///
+/// Note that the new console is a separate window and that the standard handles are not
+/// redirected to it automatically.
+///
+/// [GenerateConsoleCtrlEvent]:https://learn.microsoft.com/en-us/windows/console/generateconsolectrlevent
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub fn detach_from_parent_console() -> Result<(), Error> {
+    unsafe {
+        if FreeConsole() == 0 {
+            return Err(Error::System(io::Error::last_os_error()));
+        }
+
+        if AllocConsole() == 0 {
+            return Err(Error::System(io::Error::last_os_error()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Allocate a console for the process if it has none.
+///
+/// Console control events are only delivered to processes attached to a console, so a handler
+/// set in a process started without one, such as a GUI application or a process spawned with
+/// `DETACHED_PROCESS`, never runs. This creates a console for such processes and hides its
+/// window. Does nothing if a console is already attached.
+///
+/// Windows services do not receive console control events even with a console, see
+/// [`service_status_handle()`](fn.service_status_handle.html) for those.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+pub fn ensure_console() -> Result<(), Error> {
+    unsafe {
+        if !GetConsoleWindow().is_null() {
+            return Ok(());
+        }
+
+        if AllocConsole() == 0 {
+            return Err(Error::System(io::Error::last_os_error()));
+        }
+
+        // The return value is the previous visibility of the window, not an error.
+        ShowWindow(GetConsoleWindow(), SW_HIDE);
+    }
+
+    Ok(())
+}
+
+/// Get the status handle of the service control handler registered by
+/// [`set_handler()`](../fn.set_handler.html).
+///
+/// Only available with the `windows-service` feature. Windows services do not receive console
+/// control events, stop requests are delivered to the handler registered with
+/// `RegisterServiceCtrlHandlerEx` instead. When the process runs as a service, setting the
+/// Ctrl-C handler registers such a handler as well, which runs the Ctrl-C handler on
+/// `SERVICE_CONTROL_STOP`, `SERVICE_CONTROL_SHUTDOWN` and `SERVICE_CONTROL_PRESHUTDOWN`. The same
+/// handler therefore works whether the program runs in a console or as a service.
+///
+/// The service control handler can only be registered once the service is running, so the
+/// Ctrl-C handler must be set from the service main function. The returned handle is used to
+/// report the service status to the service control manager, which remains the responsibility of
+/// the application. Returns `None` if the process doesn't run as a service or no handler was
+/// set.
+///
+/// # Example
+/// ```no_run
+/// use std::ffi::OsString;
+///
+/// fn service_main(_arguments: Vec<OsString>) {
+///     ctrlc::set_handler(|| println!("Stopping...")).expect("Error setting Ctrl-C handler");
+///     let status_handle = ctrlc::windows::service_status_handle().expect("Not a service");
+///     // Report SERVICE_RUNNING with status_handle.set_service_status(...)
+/// }
+/// ```
+#[cfg(feature = "windows-service")]
+#[must_use = "getting the status handle has no side effects"]
+pub fn service_status_handle(
+) -> Option<windows_service::service_control_handler::ServiceStatusHandle> {
+    crate::platform::service_status_handle()
+}
+
+/// Register the service control handler of [`service_status_handle()`] for `service_name`.
+///
+/// Only available with the `windows-service` feature. [`set_handler()`](../fn.set_handler.html)
+/// registers it without a name, which only works for services running in their own process. For
+/// a service sharing its process with others, call this from its service main function, after
+/// the handler has been set.
+///
+/// # Errors
+/// Will return an error if a system error occurred.
+#[cfg(feature = "windows-service")]
+pub fn register_service_control_handler(
+    service_name: impl AsRef<std::ffi::OsStr>,
+) -> Result<windows_service::service_control_handler::ServiceStatusHandle, Error> {
+    crate::platform::register_service_handler(service_name)
+        .map_err(|e| Error::System(io::Error::new(io::ErrorKind::Other, e)))
+}
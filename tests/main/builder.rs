@@ -0,0 +1,165 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::{platform, run_harness};
+
+mod helpers;
+use helpers::counter;
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn test_stack_size() {
+    #[cfg(unix)]
+    match ctrlc::set_handler_stack_size(1) {
+        Err(ctrlc::Error::System(ref e)) if e.kind() == std::io::ErrorKind::InvalidInput => {}
+        ret => panic!("{:?}", ret),
+    }
+
+    ctrlc::set_handler_stack_size(256 * 1024).unwrap();
+
+    let (count, handler) = counter();
+    let _guard = ctrlc::set_scoped_handler(handler).unwrap();
+    count.raise_and_wait();
+    assert_eq!(count.get(), 1);
+}
+
+fn test_builder() {
+    let (count, handler) = counter();
+    let guard = ctrlc::HandlerBuilder::new()
+        .name("ctrlc-builder".into())
+        .stack_size(128 * 1024)
+        .set_scoped_handler(handler)
+        .unwrap();
+    count.raise_and_wait();
+    assert_eq!(count.get(), 1);
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let names: Vec<String> = std::fs::read_dir("/proc/self/task")
+            .unwrap()
+            .map(|task| std::fs::read_to_string(task.unwrap().path().join("comm")).unwrap())
+            .collect();
+        assert!(names.iter().any(|name| name.trim_end() == "ctrlc-builder"));
+    }
+
+    drop(guard);
+
+    #[cfg(unix)]
+    match ctrlc::HandlerBuilder::new()
+        .stack_size(1)
+        .try_set_handler(|| {})
+    {
+        Err(ctrlc::Error::System(ref e)) if e.kind() == std::io::ErrorKind::InvalidInput => {}
+        ret => panic!("{:?}", ret),
+    }
+}
+
+fn test_panic_propagation() {
+    let (panic_tx, panic_rx) = mpsc::sync_channel(1);
+    ctrlc::set_handler_with_panic_propagation(|| panic!("handler"), panic_tx).unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let payload = panic_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"handler"));
+    ctrlc::remove_all_handlers().unwrap();
+
+    let (count, handler) = counter();
+    let (panic_tx, panic_rx) = mpsc::sync_channel(1);
+    let guard = ctrlc::HandlerBuilder::new()
+        .on_panic(move |payload| panic_tx.send(payload).unwrap())
+        .set_scoped_handler(handler)
+        .unwrap();
+    count.raise_and_wait();
+    assert_eq!(count.get(), 1);
+    assert!(panic_rx.try_recv().is_err());
+    drop(guard);
+}
+
+fn test_coalesce() {
+    let min_interval = Duration::from_millis(100);
+    let (tx, rx) = mpsc::channel();
+    let guard = ctrlc::HandlerBuilder::new()
+        .coalesce(min_interval)
+        .set_scoped_handler(move || tx.send(Instant::now()).unwrap())
+        .unwrap();
+
+    for _ in 0..10 {
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+    }
+    let first = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+
+    // However late the burst is handled, no run is closer than min_interval to the previous one.
+    let second = loop {
+        match rx.try_recv() {
+            Ok(second) => break second,
+            Err(_) => unsafe {
+                platform::raise_ctrl_c();
+            },
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    assert!(second.duration_since(first) >= min_interval);
+    drop(guard);
+}
+
+fn test_handler_sigaltstack() {
+    #[cfg(unix)]
+    {
+        use nix::libc;
+
+        match ctrlc::HandlerBuilder::new()
+            .sigaltstack(0)
+            .set_scoped_handler(|| {})
+        {
+            Err(ctrlc::Error::System(_)) => {}
+            ret => panic!("{:?}", ret.map(drop)),
+        }
+        assert!(!ctrlc::is_handler_registered());
+
+        let (tx, rx) = mpsc::channel();
+        let guard = ctrlc::HandlerBuilder::new()
+            .sigaltstack(64 * 1024)
+            .set_scoped_handler(move || {
+                let mut stack = std::mem::MaybeUninit::<libc::stack_t>::uninit();
+                assert_eq!(
+                    unsafe { libc::sigaltstack(std::ptr::null(), stack.as_mut_ptr()) },
+                    0
+                );
+                tx.send(unsafe { stack.assume_init() }.ss_size).unwrap();
+            })
+            .unwrap();
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), 64 * 1024);
+        drop(guard);
+    }
+}
+
+fn tests() {
+    run_tests!(
+        test_stack_size,
+        test_builder,
+        test_panic_propagation,
+        test_coalesce,
+        test_handler_sigaltstack
+    );
+}
+
+fn main() {
+    run_harness(tests);
+}
@@ -0,0 +1,105 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::{platform, run_harness};
+
+mod helpers;
+use helpers::assert_default_disposition;
+
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+fn test_ctrl_c_future() {
+    let future = ctrlc::ctrl_c();
+
+    thread::spawn(|| {
+        thread::sleep(Duration::from_millis(100));
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+    });
+
+    block_on(future).unwrap();
+    assert!(!ctrlc::is_handler_registered());
+
+    // The handler of the first future is gone, so a second one can be awaited.
+    let future = ctrlc::ctrl_c();
+    thread::spawn(|| {
+        thread::sleep(Duration::from_millis(100));
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+    });
+    block_on(future).unwrap();
+    assert_default_disposition();
+
+    let _guard = ctrlc::set_scoped_handler(|| {}).unwrap();
+    match block_on(ctrlc::ctrl_c()) {
+        Err(ctrlc::Error::MultipleHandlers(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+}
+
+fn test_ctrl_c_future_resolved() {
+    // The handler is only removed when the future is dropped, not while it is being polled.
+    let mut future = Box::pin(ctrlc::ctrl_c());
+    thread::spawn(|| {
+        thread::sleep(Duration::from_millis(100));
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+    });
+    block_on(future.as_mut()).unwrap();
+    assert!(ctrlc::is_handler_registered());
+
+    drop(future);
+    assert!(!ctrlc::is_handler_registered());
+    assert_default_disposition();
+}
+
+fn test_ctrl_c_future_dropped() {
+    drop(ctrlc::ctrl_c());
+    assert!(!ctrlc::is_handler_registered());
+    assert_default_disposition();
+}
+
+fn tests() {
+    run_tests!(test_ctrl_c_future);
+    run_tests!(test_ctrl_c_future_resolved);
+    run_tests!(test_ctrl_c_future_dropped);
+}
+
+fn main() {
+    run_harness(tests);
+}
@@ -0,0 +1,70 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::{platform, run_harness};
+
+use std::env;
+use std::thread;
+use std::time::Duration;
+
+const CHILD_ENV: &str = "CTRLC_ESCALATION_CHILD";
+
+/// Runs in a child process, as the expected outcome is the process exiting.
+fn child() {
+    ctrlc::set_handler_with_escalation(
+        || {
+            println!("handler");
+            // A hanging graceful shutdown must not prevent the forced exit.
+            thread::sleep(Duration::from_secs(10));
+        },
+        ctrlc::EscalationPolicy::ForceExitAfter(2, 130),
+    )
+    .unwrap();
+
+    for _ in 0..2 {
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    thread::sleep(Duration::from_secs(5));
+    std::process::exit(0);
+}
+
+fn test_force_exit() {
+    // Console control events are delivered to the whole console on Windows, which would
+    // include this process.
+    #[cfg(unix)]
+    {
+        use std::process::Command;
+
+        let output = Command::new(env::current_exe().unwrap())
+            .env(CHILD_ENV, "1")
+            .output()
+            .unwrap();
+
+        assert_eq!(output.status.code(), Some(130));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "handler\n");
+    }
+}
+
+fn tests() {
+    run_tests!(test_force_exit);
+}
+
+fn main() {
+    if env::var_os(CHILD_ENV).is_some() {
+        child();
+    }
+
+    run_harness(tests);
+}
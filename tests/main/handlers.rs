@@ -0,0 +1,97 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::{platform, run_harness};
+
+mod helpers;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc, Arc,
+};
+use std::time::Duration;
+
+fn test_handler_with_info() {
+    let (tx, rx) = mpsc::channel();
+    ctrlc::set_handler_with_info(move |info| tx.send(info).unwrap()).unwrap();
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    let info = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    ctrlc::remove_all_handlers().unwrap();
+
+    assert_eq!(info.signal, ctrlc::SignalType::Ctrlc);
+    #[cfg(unix)]
+    {
+        assert_eq!(info.sender_pid, Some(std::process::id()));
+        assert_eq!(info.sender_uid, Some(unsafe { nix::libc::getuid() }));
+    }
+    #[cfg(windows)]
+    assert_eq!(info.sender_pid, None);
+}
+
+fn test_handler_dyn() {
+    use std::sync::Mutex;
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let count_handler = Arc::clone(&count);
+    let (tx, rx) = mpsc::channel();
+    let handler: Arc<Mutex<dyn FnMut() + Send>> = Arc::new(Mutex::new(move || {
+        let n = count_handler.fetch_add(1, Ordering::SeqCst);
+        tx.send(()).unwrap();
+        if n == 0 {
+            panic!("handler");
+        }
+    }));
+    ctrlc::set_handler_dyn(Arc::clone(&handler)).unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    let poison = Arc::clone(&handler);
+    let _ = std::thread::spawn(move || {
+        let _guard = poison.lock();
+        panic!("poison");
+    })
+    .join();
+    assert!(handler.is_poisoned());
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+fn test_ctrl_break() {
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+
+        let (count, handler) = helpers::counter();
+        let _guard = ctrlc::set_scoped_handler(handler).unwrap();
+        assert_ne!(unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, 0) }, 0);
+        count.wait();
+        assert_eq!(count.get(), 1);
+    }
+}
+
+fn tests() {
+    run_tests!(test_handler_with_info, test_handler_dyn, test_ctrl_break);
+}
+
+fn main() {
+    run_harness(tests);
+}
@@ -19,10 +19,26 @@ pub mod platform {
         Ok(())
     }
 
+    #[cfg(not(all(feature = "signalfd", any(target_os = "linux", target_os = "android"))))]
+    #[allow(dead_code)]
     pub unsafe fn raise_ctrl_c() {
         nix::sys::signal::raise(nix::sys::signal::SIGINT).unwrap();
     }
 
+    /// A signalfd only receives signals sent to the process or to the thread reading it, so
+    /// `raise()` can't be used.
+    #[cfg(all(feature = "signalfd", any(target_os = "linux", target_os = "android")))]
+    #[allow(dead_code)]
+    pub unsafe fn raise_ctrl_c() {
+        kill_ctrl_c();
+    }
+
+    /// Signal the process rather than the calling thread, like a terminal would.
+    #[allow(dead_code)]
+    pub unsafe fn kill_ctrl_c() {
+        nix::sys::signal::kill(nix::unistd::Pid::this(), nix::sys::signal::SIGINT).unwrap();
+    }
+
     pub unsafe fn print(fmt: ::std::fmt::Arguments) {
         use self::io::Write;
         let stdout = ::std::io::stdout();
@@ -198,6 +214,7 @@ pub mod platform {
     }
 
     /// This will signal the whole process group.
+    #[allow(dead_code)]
     pub unsafe fn raise_ctrl_c() {
         assert!(GenerateConsoleCtrlEvent(CTRL_C_EVENT, 0) != 0);
     }
@@ -228,6 +245,7 @@ macro_rules! run_tests {
     }
 }
 
+#[allow(clippy::println_empty_string)]
 pub fn run_harness(f: fn()) {
     unsafe {
         platform::setup().unwrap();
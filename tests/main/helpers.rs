@@ -0,0 +1,93 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+// Not every test binary uses every helper.
+#![allow(dead_code)]
+
+use crate::harness::platform;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+/// Counts the runs of a handler created with [`counter()`].
+pub struct Counter {
+    count: Arc<AtomicUsize>,
+    rx: mpsc::Receiver<()>,
+}
+
+impl Counter {
+    pub fn get(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Wait for the next run of the handler.
+    pub fn wait(&self) {
+        self.rx
+            .recv_timeout(Duration::from_secs(1))
+            .expect("handler did not run");
+    }
+
+    pub fn raise_and_wait(&self) {
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        self.wait();
+    }
+}
+
+pub fn counter() -> (Counter, impl FnMut() + Send + 'static) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let count_handler = Arc::clone(&count);
+    let (tx, rx) = mpsc::channel();
+    (Counter { count, rx }, move || {
+        count_handler.fetch_add(1, Ordering::SeqCst);
+        let _ = tx.send(());
+    })
+}
+
+/// Raise Ctrl-C from another thread once a handler is registered, e.g. by a function that only
+/// returns after Ctrl-C.
+pub fn raise_when_registered() -> thread::JoinHandle<()> {
+    thread::spawn(|| {
+        // With a signalfd, every thread must block the signal for it to be read from the fd.
+        // This one was spawned before the handler was registered.
+        #[cfg(all(feature = "signalfd", any(target_os = "linux", target_os = "android")))]
+        {
+            use nix::sys::signal::{pthread_sigmask, SigSet, SigmaskHow, Signal};
+
+            let mut mask = SigSet::empty();
+            mask.add(Signal::SIGINT);
+            pthread_sigmask(SigmaskHow::SIG_BLOCK, Some(&mask), None).unwrap();
+        }
+        while !ctrlc::is_handler_registered() {
+            thread::yield_now();
+        }
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+    })
+}
+
+#[cfg(all(unix, not(feature = "signalfd")))]
+pub fn assert_default_disposition() {
+    use nix::libc;
+
+    unsafe {
+        let mut old = std::mem::MaybeUninit::<libc::sigaction>::uninit();
+        assert_eq!(
+            libc::sigaction(libc::SIGINT, std::ptr::null(), old.as_mut_ptr()),
+            0
+        );
+        assert_eq!(old.assume_init().sa_sigaction, libc::SIG_DFL);
+    }
+}
+
+#[cfg(not(all(unix, not(feature = "signalfd"))))]
+pub fn assert_default_disposition() {}
@@ -0,0 +1,123 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::{platform, run_harness};
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+fn test_inject_signal() {
+    match ctrlc::testing::inject_signal(ctrlc::SignalType::Ctrlc) {
+        Err(ctrlc::Error::System(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+
+    match ctrlc::testing::simulate_ctrl_c() {
+        Err(ctrlc::Error::System(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let count_handler = Arc::clone(&count);
+    ctrlc::set_handler(move || {
+        count_handler.fetch_add(1, Ordering::SeqCst);
+    })
+    .unwrap();
+
+    ctrlc::testing::inject_signal(ctrlc::SignalType::Ctrlc).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    ctrlc::testing::inject_signal(ctrlc::SignalType::Ctrlc).unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+
+    ctrlc::testing::simulate_ctrl_c().unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(count.load(Ordering::SeqCst), 4);
+
+    match ctrlc::testing::inject_signal(ctrlc::SignalType::Other(signal())) {
+        Err(ctrlc::Error::NoSuchSignal(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+fn test_inject_termination() {
+    #[cfg(feature = "termination")]
+    {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let (tx, rx) = mpsc::channel();
+        ctrlc::set_handler_with_info(move |info| tx.send(info.signal).unwrap()).unwrap();
+
+        ctrlc::testing::inject_signal(ctrlc::SignalType::Termination).unwrap();
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(1)).unwrap(),
+            ctrlc::SignalType::Termination
+        );
+        ctrlc::remove_all_handlers().unwrap();
+    }
+}
+
+fn test_flag_register() {
+    #[cfg(feature = "termination")]
+    {
+        use std::sync::atomic::AtomicBool;
+        use std::time::{Duration, Instant};
+
+        let flag = Arc::new(AtomicBool::new(false));
+        ctrlc::flag::register(ctrlc::SignalType::Termination, Arc::clone(&flag)).unwrap();
+
+        ctrlc::testing::inject_signal(ctrlc::SignalType::Ctrlc).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!flag.load(Ordering::SeqCst));
+
+        ctrlc::testing::inject_signal(ctrlc::SignalType::Termination).unwrap();
+        let start = Instant::now();
+        while !flag.load(Ordering::SeqCst) {
+            assert!(start.elapsed() < Duration::from_secs(1));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        ctrlc::remove_all_handlers().unwrap();
+    }
+}
+
+#[cfg(unix)]
+fn signal() -> ctrlc::Signal {
+    ctrlc::Signal::SIGUSR1
+}
+
+#[cfg(windows)]
+fn signal() -> ctrlc::Signal {
+    windows_sys::Win32::System::Console::CTRL_LOGOFF_EVENT
+}
+
+fn tests() {
+    run_tests!(test_inject_signal);
+    run_tests!(test_inject_termination);
+    run_tests!(test_flag_register);
+}
+
+fn main() {
+    run_harness(tests);
+}
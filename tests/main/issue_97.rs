@@ -17,7 +17,7 @@ use test_signal_hook::run_signal_hook;
 fn expect_multiple_handlers() {
     #[cfg(not(windows))]
     match ctrlc::try_set_handler(|| {}) {
-        Err(ctrlc::Error::MultipleHandlers) => {}
+        Err(ctrlc::Error::MultipleHandlers(ctrlc::Signal::SIGINT)) => {}
         _ => panic!("Expected Error::MultipleHandlers"),
     }
 }
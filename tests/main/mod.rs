@@ -32,13 +32,298 @@ fn test_set_handler() {
     assert!(flag.load(Ordering::SeqCst));
 
     match ctrlc::set_handler(|| {}) {
-        Err(ctrlc::Error::MultipleHandlers) => {}
+        Err(ctrlc::Error::MultipleHandlers(_)) => {}
         ret => panic!("{:?}", ret),
     }
 }
 
+fn test_current_handler() {
+    #[cfg(unix)]
+    {
+        use ctrlc::unix::current_handler;
+
+        let action = current_handler(ctrlc::SignalType::Ctrlc).unwrap();
+        assert_eq!(action.is_ctrlc(), !cfg!(feature = "signalfd"));
+        assert!(!action.is_ignored());
+
+        let action = current_handler(ctrlc::SignalType::WindowChange).unwrap();
+        assert!(action.is_default());
+        assert!(!action.is_ctrlc());
+    }
+}
+
+fn test_signal_mask() {
+    #[cfg(unix)]
+    std::thread::spawn(|| {
+        use nix::sys::signal::{raise, Signal};
+
+        let guard = ctrlc::unix::SignalMask::block_all().unwrap();
+        raise(Signal::SIGINT).unwrap();
+        assert!(ctrlc::unix::is_signal_pending(ctrlc::SignalType::Ctrlc).unwrap());
+        drop(guard);
+
+        #[cfg(not(feature = "signalfd"))]
+        assert!(!ctrlc::unix::is_signal_pending(ctrlc::SignalType::Ctrlc).unwrap());
+    })
+    .join()
+    .unwrap();
+}
+
+fn test_signal_unmask() {
+    #[cfg(all(unix, not(feature = "signalfd")))]
+    std::thread::spawn(|| {
+        use nix::sys::signal::{pthread_sigmask, SigSet, SigmaskHow, Signal};
+
+        let mut mask = SigSet::empty();
+        mask.add(Signal::SIGINT);
+        pthread_sigmask(SigmaskHow::SIG_BLOCK, Some(&mask), None).unwrap();
+
+        let guard = ctrlc::unix::SignalMask::unblock_all().unwrap();
+        assert!(!SigSet::thread_get_mask().unwrap().contains(Signal::SIGINT));
+        drop(guard);
+        assert!(SigSet::thread_get_mask().unwrap().contains(Signal::SIGINT));
+    })
+    .join()
+    .unwrap();
+}
+
+fn test_signal_names() {
+    #[cfg(unix)]
+    for signal in ctrlc::Signal::iterator() {
+        let name = ctrlc::unix::signal_number_to_name(signal as i32).unwrap();
+        assert_eq!(name, signal.as_str());
+        assert_eq!(
+            ctrlc::unix::signal_name_to_number(&name.to_lowercase()),
+            Some(signal as i32)
+        );
+    }
+}
+
+fn test_signal_type_strings() {
+    use ctrlc::SignalType;
+    use std::collections::HashSet;
+
+    #[cfg(unix)]
+    let (other, foreign) = (ctrlc::Signal::SIGALRM, "CTRL_BREAK_EVENT");
+    #[cfg(windows)]
+    let (other, foreign) = (
+        windows_sys::Win32::System::Console::CTRL_BREAK_EVENT,
+        "SIGUSR1",
+    );
+
+    let signals = [
+        SignalType::Ctrlc,
+        SignalType::Termination,
+        #[cfg(unix)]
+        SignalType::Hangup,
+        #[cfg(unix)]
+        SignalType::Quit,
+        #[cfg(unix)]
+        SignalType::WindowChange,
+        #[cfg(unix)]
+        SignalType::UserDefined1,
+        #[cfg(unix)]
+        SignalType::UserDefined2,
+        SignalType::Other(other),
+    ];
+    for signal in signals {
+        assert_eq!(signal.to_string().parse::<SignalType>(), Ok(signal));
+        assert_eq!(signal.to_string().to_lowercase().parse(), Ok(signal));
+    }
+
+    assert_eq!(
+        foreign.parse::<SignalType>(),
+        Err(ctrlc::UnknownSignal(foreign.into()))
+    );
+
+    let set: HashSet<SignalType> = signals.iter().chain(signals.iter()).copied().collect();
+    assert_eq!(set.len(), signals.len());
+}
+
+fn test_signal_type_into_signal() {
+    #[cfg(unix)]
+    {
+        use ctrlc::{Signal, SignalType};
+
+        for (signal_type, signal) in [
+            (SignalType::Ctrlc, Signal::SIGINT),
+            (SignalType::Termination, Signal::SIGTERM),
+            (SignalType::Hangup, Signal::SIGHUP),
+            (SignalType::Quit, Signal::SIGQUIT),
+            (SignalType::WindowChange, Signal::SIGWINCH),
+            (SignalType::UserDefined1, Signal::SIGUSR1),
+            (SignalType::UserDefined2, Signal::SIGUSR2),
+            (SignalType::Other(Signal::SIGALRM), Signal::SIGALRM),
+        ] {
+            assert_eq!(Signal::from(signal_type), signal);
+            if signal != Signal::SIGTERM {
+                assert_eq!(SignalType::from(signal), signal_type);
+            }
+        }
+    }
+}
+
+fn test_signal_type_raw() {
+    use ctrlc::SignalType;
+
+    #[cfg(unix)]
+    let (ctrl_c, other) = (nix::libc::SIGINT, SignalType::Other(ctrlc::Signal::SIGALRM));
+    #[cfg(windows)]
+    let (ctrl_c, other) = (
+        0u32 as i32,
+        SignalType::Other(windows_sys::Win32::System::Console::CTRL_BREAK_EVENT),
+    );
+
+    assert_eq!(SignalType::Ctrlc.into_raw(), ctrl_c);
+    assert_eq!(SignalType::from_raw(ctrl_c), Some(SignalType::Ctrlc));
+    assert_eq!(SignalType::from_raw(other.into_raw()), Some(other));
+    assert_eq!(
+        SignalType::from_raw(SignalType::Termination.into_raw()),
+        Some(SignalType::Termination)
+    );
+    assert_eq!(SignalType::from_raw(-1), None);
+    assert_eq!(SignalType::from_raw(1000), None);
+}
+
+fn test_signal_type_aliases() {
+    use ctrlc::SignalType;
+
+    assert_eq!(SignalType::CTRL_C, SignalType::Ctrlc);
+    assert_eq!(ctrlc::CTRL_C_SIGNAL, SignalType::Ctrlc);
+    #[cfg(unix)]
+    {
+        assert_eq!(SignalType::SIGINT, SignalType::Ctrlc);
+        assert_eq!(SignalType::SIGTERM, SignalType::Termination);
+    }
+}
+
+fn test_sys() {
+    use ctrlc::sys;
+
+    assert!(sys::signal_iterator().any(|s| s == sys::CTRL_C_SIGNAL));
+    assert!(sys::signal_iterator().any(|s| s == sys::TERMINATION_SIGNAL));
+
+    #[cfg(unix)]
+    {
+        use nix::sys::signal::{signal, SigHandler, Signal};
+
+        unsafe { signal(Signal::SIGWINCH, SigHandler::SigIgn) }.unwrap();
+        sys::revert_sighandler_to_default(Signal::SIGWINCH).unwrap();
+        let action = ctrlc::unix::current_handler(ctrlc::SignalType::WindowChange).unwrap();
+        assert!(action.is_default());
+
+        assert!(sys::revert_sighandler_to_default(Signal::SIGKILL).is_err());
+    }
+}
+
+fn test_error_source() {
+    use std::error::Error;
+
+    let e = ctrlc::Error::System(std::io::Error::from_raw_os_error(22));
+    assert!(e.source().is_some());
+    assert!(e.is_os_error());
+    let e = ctrlc::Error::MultipleHandlers(ctrlc::sys::CTRL_C_SIGNAL);
+    assert!(e.source().is_none());
+    assert!(!e.is_os_error());
+    #[cfg(unix)]
+    assert_eq!(
+        e.to_string(),
+        "Ctrl-C error: Signal SIGINT already has a handler"
+    );
+
+    assert_eq!(e, ctrlc::Error::MultipleHandlers(ctrlc::sys::CTRL_C_SIGNAL));
+    assert_ne!(e, ctrlc::Error::NoSuchSignal(ctrlc::SignalType::Ctrlc));
+    assert_eq!(
+        ctrlc::Error::System(std::io::Error::from_raw_os_error(22)),
+        ctrlc::Error::System(std::io::Error::from_raw_os_error(22))
+    );
+    assert_ne!(
+        ctrlc::Error::System(std::io::Error::from_raw_os_error(22)),
+        ctrlc::Error::System(std::io::Error::from_raw_os_error(2))
+    );
+
+    #[cfg(unix)]
+    match ctrlc::Error::from(nix::errno::Errno::EINVAL) {
+        ctrlc::Error::System(e) => assert_eq!(e.raw_os_error(), Some(nix::libc::EINVAL)),
+        e => panic!("{:?}", e),
+    }
+}
+
+fn test_signal_pending() {
+    #[cfg(unix)]
+    std::thread::spawn(|| {
+        use nix::sys::signal::{pthread_sigmask, raise, SigSet, SigmaskHow, Signal};
+
+        let signal = ctrlc::SignalType::WindowChange;
+        let mut mask = SigSet::empty();
+        mask.add(Signal::SIGWINCH);
+
+        pthread_sigmask(SigmaskHow::SIG_BLOCK, Some(&mask), None).unwrap();
+        raise(Signal::SIGWINCH).unwrap();
+        assert!(ctrlc::unix::is_signal_pending(signal).unwrap());
+        assert!(!ctrlc::unix::is_signal_pending(ctrlc::SignalType::Ctrlc).unwrap());
+
+        pthread_sigmask(SigmaskHow::SIG_UNBLOCK, Some(&mask), None).unwrap();
+        let signal = ctrlc::SignalType::WindowChange;
+        assert!(!ctrlc::unix::is_signal_pending(signal).unwrap());
+
+        // SIGHUP belongs to Hangup only. Consume it with sigwait(), unblocking it would terminate
+        // the process.
+        let mut mask = SigSet::empty();
+        mask.add(Signal::SIGHUP);
+        pthread_sigmask(SigmaskHow::SIG_BLOCK, Some(&mask), None).unwrap();
+        raise(Signal::SIGHUP).unwrap();
+        assert!(ctrlc::unix::is_signal_pending(ctrlc::SignalType::Hangup).unwrap());
+        assert!(!ctrlc::unix::is_signal_pending(ctrlc::SignalType::Termination).unwrap());
+        assert_eq!(mask.wait().unwrap(), Signal::SIGHUP);
+    })
+    .join()
+    .unwrap();
+}
+
+fn test_sigaltstack() {
+    #[cfg(unix)]
+    std::thread::spawn(|| {
+        assert!(ctrlc::unix::configure_sigaltstack(0).is_err());
+        let guard = ctrlc::unix::configure_sigaltstack(64 * 1024).unwrap();
+        drop(guard);
+    })
+    .join()
+    .unwrap();
+}
+
+fn test_signal_fd_wrapper() {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    std::thread::spawn(|| {
+        use nix::sys::signal::{raise, Signal};
+
+        let signal = ctrlc::SignalType::Other(Signal::SIGUSR2);
+        let mut fd = ctrlc::unix::SignalFd::new(&[signal]).unwrap();
+
+        raise(Signal::SIGUSR2).unwrap();
+        let info = fd.read_siginfo().unwrap();
+        assert_eq!(info.ssi_signo, Signal::SIGUSR2 as u32);
+        assert_eq!(info.ssi_pid, std::process::id());
+    })
+    .join()
+    .unwrap();
+}
+
 fn tests() {
     run_tests!(test_set_handler);
+    run_tests!(test_current_handler);
+    run_tests!(test_signal_mask);
+    run_tests!(test_signal_unmask);
+    run_tests!(test_signal_names);
+    run_tests!(test_signal_type_strings);
+    run_tests!(test_signal_type_into_signal);
+    run_tests!(test_signal_type_raw);
+    run_tests!(test_signal_type_aliases);
+    run_tests!(test_sys);
+    run_tests!(test_error_source);
+    run_tests!(test_signal_pending);
+    run_tests!(test_sigaltstack);
+    run_tests!(test_signal_fd_wrapper);
 }
 
 fn main() {
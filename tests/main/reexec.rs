@@ -0,0 +1,89 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::{platform, run_harness};
+
+use std::env;
+use std::sync::mpsc;
+use std::time::Duration;
+
+const CHILD_ENV: &str = "CTRLC_REEXEC_CHILD";
+
+/// Runs in a process re-executed from the test, which starts over with a fresh `INIT` and the
+/// default signal dispositions.
+fn child() {
+    #[cfg(unix)]
+    {
+        let action = ctrlc::unix::current_handler(ctrlc::SignalType::Ctrlc).unwrap();
+        assert!(action.is_default());
+    }
+    assert!(!ctrlc::is_handler_registered());
+
+    let (tx, rx) = mpsc::channel();
+    ctrlc::set_handler(move || tx.send(()).unwrap()).unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    println!("handler");
+    std::process::exit(0);
+}
+
+fn test_reexec() {
+    // Console control events are delivered to the whole console on Windows, which would
+    // include this process.
+    #[cfg(unix)]
+    {
+        use std::process::Command;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::thread;
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_handler = Arc::clone(&count);
+        ctrlc::set_handler(move || {
+            count_handler.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        // Forks and execs with the handler registered in this process.
+        let output = Command::new(env::current_exe().unwrap())
+            .env(CHILD_ENV, "1")
+            .output()
+            .unwrap();
+
+        assert!(output.status.success(), "{:?}", output);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "handler\n");
+
+        // The handler of the child ran independently, this process saw no signal.
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        unsafe {
+            platform::raise_ctrl_c();
+        }
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
+
+fn tests() {
+    run_tests!(test_reexec);
+}
+
+fn main() {
+    if env::var_os(CHILD_ENV).is_some() {
+        child();
+    }
+
+    run_harness(tests);
+}
@@ -0,0 +1,149 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::{platform, run_harness};
+
+mod helpers;
+use helpers::{assert_default_disposition, counter};
+
+use std::thread;
+use std::time::Duration;
+
+fn test_scoped_handler() {
+    let (count, handler) = counter();
+    let guard = ctrlc::set_scoped_handler(handler).unwrap();
+    count.raise_and_wait();
+    assert_eq!(count.get(), 1);
+
+    drop(guard);
+    assert_default_disposition();
+
+    // Stale guards don't remove handlers registered after theirs.
+    let (_, handler) = counter();
+    let stale = ctrlc::try_set_scoped_handler(handler).unwrap();
+    ctrlc::remove_all_handlers().unwrap();
+    let (count, handler) = counter();
+    let guard = ctrlc::try_set_scoped_handler(handler).unwrap();
+    drop(stale);
+    count.raise_and_wait();
+    assert_eq!(count.get(), 1);
+    drop(guard);
+
+    ctrlc::remove_all_handlers().unwrap();
+    ctrlc::remove_all_handlers().unwrap();
+    assert_default_disposition();
+}
+
+fn test_detach() {
+    let (count, handler) = counter();
+    ctrlc::set_scoped_handler(handler).unwrap().detach();
+
+    match ctrlc::try_set_handler(|| {}) {
+        Err(ctrlc::Error::MultipleHandlers(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+
+    count.raise_and_wait();
+    assert_eq!(count.get(), 1);
+
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+fn test_remove_from_handler() {
+    let (count, mut handler) = counter();
+    ctrlc::set_handler(move || {
+        ctrlc::remove_all_handlers().unwrap();
+        handler();
+    })
+    .unwrap();
+
+    count.raise_and_wait();
+    assert_eq!(count.get(), 1);
+    assert_default_disposition();
+
+    let (count, handler) = counter();
+    let _guard = ctrlc::set_scoped_handler(handler).unwrap();
+    count.raise_and_wait();
+    assert_eq!(count.get(), 1);
+}
+
+fn test_remove_race() {
+    let (entered_tx, entered_rx) = std::sync::mpsc::channel();
+    let handle = ctrlc::set_handler_once(move || {
+        entered_tx.send(()).unwrap();
+        // Still running when the handler is removed below, after which it removes itself too.
+        thread::sleep(Duration::from_millis(200));
+    })
+    .unwrap();
+
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    entered_rx.recv().unwrap();
+    ctrlc::remove_all_handlers().unwrap();
+
+    handle.join().unwrap();
+    assert!(!ctrlc::is_handler_registered());
+    assert_default_disposition();
+}
+
+fn test_handler_once() {
+    let handle = ctrlc::set_handler_once(|| 42).unwrap();
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    assert_eq!(handle.join().unwrap(), Some(42));
+    assert_default_disposition();
+
+    let handle = ctrlc::set_handler_once(|| 42).unwrap();
+    ctrlc::remove_all_handlers().unwrap();
+    assert_eq!(handle.join().unwrap(), None);
+
+    let handle = ctrlc::set_handler_once(|| panic!("handler")).unwrap();
+    unsafe {
+        platform::raise_ctrl_c();
+    }
+    assert!(handle.join().is_err());
+    assert_default_disposition();
+}
+
+fn test_is_handler_registered() {
+    assert!(!ctrlc::is_handler_registered());
+    assert_eq!(ctrlc::handler_thread_name(), None);
+
+    let guard = ctrlc::set_scoped_handler(|| {}).unwrap();
+    assert!(ctrlc::is_handler_registered());
+    assert_eq!(ctrlc::handler_thread_name(), Some("ctrl-c"));
+    drop(guard);
+    assert!(!ctrlc::is_handler_registered());
+    assert_eq!(ctrlc::handler_thread_name(), None);
+
+    let _guard = ctrlc::HandlerBuilder::new()
+        .name("shutdown".into())
+        .set_scoped_handler(|| {})
+        .unwrap();
+    assert_eq!(ctrlc::handler_thread_name(), Some("shutdown"));
+}
+
+fn tests() {
+    run_tests!(
+        test_scoped_handler,
+        test_detach,
+        test_remove_from_handler,
+        test_remove_race,
+        test_handler_once,
+        test_is_handler_registered
+    );
+}
+
+fn main() {
+    run_harness(tests);
+}
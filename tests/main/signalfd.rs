@@ -0,0 +1,112 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::run_harness;
+
+fn test_signal_fd() {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use nix::libc;
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        assert_eq!(ctrlc::unix::signal_fd(), -1);
+
+        // Keep the signal handling thread busy after the first signal so that the second one
+        // stays queued in the signalfd.
+        let (tx, rx) = mpsc::channel();
+        ctrlc::set_handler(move || {
+            tx.send(()).unwrap();
+            loop {
+                std::thread::park();
+            }
+        })
+        .unwrap();
+
+        let fd = ctrlc::unix::signal_fd();
+        assert!(fd >= 0);
+
+        unsafe {
+            harness::platform::kill_ctrl_c();
+        }
+        rx.recv_timeout(Duration::from_secs(1)).unwrap();
+
+        unsafe {
+            harness::platform::kill_ctrl_c();
+        }
+
+        let mut pollfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 1000) }, 1);
+        assert!(pollfd.revents & libc::POLLIN != 0);
+
+        let mut info = std::mem::MaybeUninit::<libc::signalfd_siginfo>::uninit();
+        let size = std::mem::size_of::<libc::signalfd_siginfo>();
+        let n = unsafe { libc::read(fd, info.as_mut_ptr().cast(), size) };
+        assert_eq!(n as usize, size);
+        let info = unsafe { info.assume_init() };
+        assert_eq!(info.ssi_signo, libc::SIGINT as u32);
+        assert_eq!(info.ssi_pid, std::process::id());
+    }
+}
+
+fn test_remove_restores_mask() {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use nix::sys::signal::{kill, SigSet, Signal};
+        use nix::unistd::Pid;
+        use std::sync::mpsc;
+        use std::thread;
+        use std::time::Duration;
+
+        let blocked = || SigSet::thread_get_mask().unwrap().contains(Signal::SIGINT);
+        assert!(!blocked());
+
+        let (entered_tx, entered_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let guard = ctrlc::set_scoped_handler(move || {
+            if entered_tx.send(()).is_ok() {
+                let _ = release_rx.recv();
+            }
+        })
+        .unwrap();
+        assert!(blocked());
+
+        kill(Pid::this(), Signal::SIGINT).unwrap();
+        entered_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        drop(entered_rx);
+
+        // Stays in the signalfd while the handler is busy. It must be discarded on removal
+        // rather than terminate the process once SIGINT is unblocked.
+        kill(Pid::this(), Signal::SIGINT).unwrap();
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            drop(release_tx);
+        });
+        drop(guard);
+        releaser.join().unwrap();
+
+        assert!(!blocked());
+        assert_eq!(ctrlc::unix::signal_fd(), -1);
+    }
+}
+
+fn tests() {
+    run_tests!(test_remove_restores_mask);
+    run_tests!(test_signal_fd);
+}
+
+fn main() {
+    run_harness(tests);
+}
@@ -0,0 +1,190 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::run_harness;
+
+mod helpers;
+#[cfg(unix)]
+use helpers::counter;
+
+fn test_replace_ignored() {
+    #[cfg(unix)]
+    {
+        use nix::libc;
+
+        unsafe {
+            assert_ne!(libc::signal(libc::SIGINT, libc::SIG_IGN), libc::SIG_ERR);
+        }
+
+        match ctrlc::set_handler_with_conflict_policy(|| {}, ctrlc::ConflictPolicy::ErrorOnAny) {
+            Err(ctrlc::Error::MultipleHandlers(_)) => {}
+            ret => panic!("{:?}", ret),
+        }
+
+        let (count, handler) = counter();
+        let guard = ctrlc::try_set_scoped_handler(handler).unwrap();
+        count.raise_and_wait();
+        assert_eq!(count.get(), 1);
+        drop(guard);
+    }
+}
+
+fn test_quit() {
+    #[cfg(all(unix, feature = "termination"))]
+    {
+        use nix::sys::signal::{kill, Signal};
+        use nix::unistd::Pid;
+
+        let (count, handler) = counter();
+        let _guard = ctrlc::set_scoped_handler(handler).unwrap();
+        kill(Pid::this(), Signal::SIGQUIT).unwrap();
+        count.wait();
+        assert_eq!(count.get(), 1);
+    }
+}
+
+fn test_fork() {
+    #[cfg(unix)]
+    {
+        use nix::libc;
+
+        let (count, handler) = counter();
+        let _guard = ctrlc::set_scoped_handler(handler).unwrap();
+
+        unsafe {
+            match libc::fork() {
+                -1 => panic!("{}", std::io::Error::last_os_error()),
+                0 => {
+                    let ok = ctrlc::set_scoped_handler(|| {}).is_ok();
+                    libc::_exit(if ok { 0 } else { 1 });
+                }
+                pid => {
+                    let mut status = 0;
+                    assert_eq!(libc::waitpid(pid, &mut status, 0), pid);
+                    assert!(libc::WIFEXITED(status));
+                    assert_eq!(libc::WEXITSTATUS(status), 0);
+                }
+            }
+        }
+
+        count.raise_and_wait();
+        assert_eq!(count.get(), 1);
+    }
+}
+
+fn test_block_with_sigset() {
+    #[cfg(all(unix, not(feature = "signalfd")))]
+    {
+        use nix::sys::signal::{kill, SigSet, Signal};
+        use nix::unistd::Pid;
+        use std::thread;
+        use std::time::Duration;
+
+        let (count, handler) = counter();
+        let guard = ctrlc::set_scoped_handler(handler).unwrap();
+
+        // SIGINT is blocked in the waiter, so the signal sent to the process is delivered to
+        // another thread and the wait has to end through the handler thread.
+        let waiter = thread::spawn(|| {
+            let mask = SigSet::thread_get_mask().unwrap();
+            unsafe { ctrlc::unix::block_ctrl_c_with_sigset(&SigSet::all()) }.unwrap();
+            assert_eq!(SigSet::thread_get_mask().unwrap(), mask);
+        });
+        // The waiter may not be waiting yet, keep signaling until it returns.
+        while !waiter.is_finished() {
+            kill(Pid::this(), Signal::SIGINT).unwrap();
+            thread::sleep(Duration::from_millis(10));
+        }
+        waiter.join().unwrap();
+        assert!(count.get() >= 1);
+
+        let waiter =
+            thread::spawn(|| unsafe { ctrlc::unix::block_ctrl_c_with_sigset(&SigSet::empty()) });
+        drop(guard);
+        match waiter.join().unwrap() {
+            Err(ctrlc::Error::System(e)) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::NotFound)
+            }
+            ret => panic!("{:?}", ret),
+        }
+    }
+}
+
+fn test_blocked_in_setting_thread() {
+    #[cfg(all(unix, not(feature = "signalfd")))]
+    {
+        use nix::sys::signal::{kill, pthread_sigmask, SigSet, SigmaskHow, Signal};
+        use nix::unistd::Pid;
+
+        let mut mask = SigSet::empty();
+        mask.add(Signal::SIGINT);
+        let mut old_mask = SigSet::empty();
+        pthread_sigmask(SigmaskHow::SIG_BLOCK, Some(&mask), Some(&mut old_mask)).unwrap();
+
+        // raise() would leave the signal pending in this thread.
+        let (count, handler) = counter();
+        let guard = ctrlc::set_scoped_handler(handler).unwrap();
+        kill(Pid::this(), Signal::SIGINT).unwrap();
+        count.wait();
+        assert_eq!(count.get(), 1);
+        drop(guard);
+
+        pthread_sigmask(SigmaskHow::SIG_SETMASK, Some(&old_mask), None).unwrap();
+    }
+}
+
+fn test_dropped_signals() {
+    #[cfg(all(unix, not(feature = "signalfd")))]
+    {
+        use nix::sys::signal::{raise, Signal};
+        use std::sync::mpsc;
+
+        let (entered_tx, entered_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let guard = ctrlc::set_scoped_handler(move || {
+            if entered_tx.send(()).is_ok() {
+                let _ = release_rx.recv();
+            }
+        })
+        .unwrap();
+
+        raise(Signal::SIGINT).unwrap();
+        entered_rx.recv().unwrap();
+        drop(entered_rx);
+
+        // raise() runs the OS handler before returning, so every signal has been written to
+        // the pipe, or dropped, once the loop is done. A 64 KiB pipe holds about 5500
+        // signals, raise a few more than that.
+        let dropped = ctrlc::unix::dropped_signal_count();
+        for _ in 0..6_000 {
+            raise(Signal::SIGINT).unwrap();
+        }
+        assert!(ctrlc::unix::dropped_signal_count() > dropped);
+
+        drop(release_tx);
+        drop(guard);
+    }
+}
+
+fn tests() {
+    run_tests!(
+        test_replace_ignored,
+        test_quit,
+        test_fork,
+        test_block_with_sigset,
+        test_blocked_in_setting_thread,
+        test_dropped_signals
+    );
+}
+
+fn main() {
+    run_harness(tests);
+}
@@ -0,0 +1,121 @@
+// Copyright (c) 2023 CtrlC developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+#[macro_use]
+mod harness;
+use harness::{platform, run_harness};
+
+mod helpers;
+use helpers::{assert_default_disposition, counter, raise_when_registered};
+
+use std::thread;
+use std::time::Duration;
+
+fn test_run_until_ctrl_c() {
+    let mut n = 0;
+    ctrlc::util::run_until_ctrl_c(|| {
+        n += 1;
+        n < 3
+    })
+    .unwrap();
+    assert_eq!(n, 3);
+
+    let mut n = 0;
+    ctrlc::util::run_until_ctrl_c(|| {
+        n += 1;
+        if n == 3 {
+            unsafe {
+                platform::raise_ctrl_c();
+            }
+        }
+        thread::sleep(Duration::from_millis(10));
+        true
+    })
+    .unwrap();
+    assert!(n >= 3);
+    assert_default_disposition();
+}
+
+fn test_block_until_ctrl_c() {
+    let raiser = raise_when_registered();
+    ctrlc::block_until_ctrl_c().unwrap();
+    raiser.join().unwrap();
+    assert_default_disposition();
+
+    let _guard = ctrlc::set_scoped_handler(|| {}).unwrap();
+    match ctrlc::block_until_ctrl_c() {
+        Err(ctrlc::Error::MultipleHandlers(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+}
+
+fn test_exponential_backoff() {
+    let backoff = |f: &mut dyn FnMut() -> bool| {
+        ctrlc::util::exponential_backoff_on_signal(
+            Duration::from_millis(10),
+            Duration::from_millis(40),
+            f,
+        )
+    };
+
+    let mut attempts = 0;
+    backoff(&mut || {
+        attempts += 1;
+        attempts == 3
+    })
+    .unwrap();
+    assert_eq!(attempts, 3);
+
+    // A zero interval still waits between attempts.
+    let mut attempts = 0;
+    let start = std::time::Instant::now();
+    ctrlc::util::exponential_backoff_on_signal(Duration::ZERO, Duration::ZERO, || {
+        attempts += 1;
+        attempts == 5
+    })
+    .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(4));
+
+    // Ctrl-C ends the wait right away rather than after the interval.
+    let raiser = raise_when_registered();
+    let start = std::time::Instant::now();
+    ctrlc::util::exponential_backoff_on_signal(
+        Duration::from_secs(10),
+        Duration::from_secs(10),
+        || false,
+    )
+    .unwrap();
+    raiser.join().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_default_disposition();
+}
+
+fn test_double_ctrl_c_exit() {
+    #[cfg(unix)]
+    assert_eq!(ctrlc::SIGINT_EXIT_CODE, 128 + nix::libc::SIGINT);
+
+    let (count, handler) = counter();
+    ctrlc::install_double_ctrl_c_exit_with("Shutting down...", handler).unwrap();
+    count.raise_and_wait();
+    assert_eq!(count.get(), 1);
+    ctrlc::remove_all_handlers().unwrap();
+}
+
+fn tests() {
+    run_tests!(
+        test_run_until_ctrl_c,
+        test_block_until_ctrl_c,
+        test_exponential_backoff,
+        test_double_ctrl_c_exit
+    );
+}
+
+fn main() {
+    run_harness(tests);
+}